@@ -0,0 +1,103 @@
+package go_groth16
+
+/*
+#cgo CFLAGS:-I${SRCDIR}/libgroth16/include
+#include <stdlib.h>
+#include <groth16.h>
+
+void goGroth16JobDone(uint64_t job_id, int status, char *output, void *user_data);
+*/
+import "C"
+import (
+	"errors"
+	"sync"
+	"time"
+	"unsafe"
+)
+
+// JobStatus is the state of a job queued by ProveAsync.
+type JobStatus int
+
+const (
+	// JobUnknown is reported once the job's result was delivered and for unknown ids.
+	JobUnknown JobStatus = C.GROTH16_JOB_UNKNOWN
+	JobQueued  JobStatus = C.GROTH16_JOB_QUEUED
+	JobRunning JobStatus = C.GROTH16_JOB_RUNNING
+)
+
+var (
+	// ErrJobCancelled is the error of a job stopped by CancelJob.
+	ErrJobCancelled = errors.New("libgroth16: job cancelled")
+	// ErrJobTimedOut is the error of a job that ran past its deadline.
+	ErrJobTimedOut = errors.New("libgroth16: job timed out")
+)
+
+// ProveResult is the outcome of a job queued by ProveAsync.
+type ProveResult struct {
+	Output string
+	Err    error
+}
+
+var (
+	jobsMu sync.Mutex
+	jobs   = map[uint64]chan ProveResult{}
+)
+
+//export goGroth16JobDone
+func goGroth16JobDone(jobID C.uint64_t, status C.int, output *C.char, _ unsafe.Pointer) {
+	// Waits for ProveAsync to register the job, should it finish right away.
+	jobsMu.Lock()
+	result, ok := jobs[uint64(jobID)]
+	delete(jobs, uint64(jobID))
+	jobsMu.Unlock()
+	if !ok {
+		return
+	}
+	switch status {
+	case 0:
+		result <- ProveResult{Output: C.GoString(output)}
+	case -3:
+		result <- ProveResult{Err: ErrJobCancelled}
+	case -4:
+		result <- ProveResult{Err: ErrJobTimedOut}
+	default:
+		result <- ProveResult{Err: errors.New(C.GoString(output))}
+	}
+}
+
+// ProveAsync queues a proof on the library's worker pool, without blocking an OS thread while it runs. It returns the
+// job id and a channel that receives the result. For a positive timeout, the result is ErrJobTimedOut once the job took
+// longer than timeout, counted from now.
+func (c *ProvingContext) ProveAsync(input string, timeout time.Duration) (uint64, <-chan ProveResult, error) {
+	var timeoutMs int64
+	if timeout > 0 {
+		timeoutMs = timeout.Milliseconds()
+		if timeoutMs < 1 {
+			timeoutMs = 1
+		}
+	}
+	inputC := C.CString(input)
+	defer C.free(unsafe.Pointer(inputC))
+	result := make(chan ProveResult, 1)
+
+	jobsMu.Lock()
+	defer jobsMu.Unlock()
+	callback := (C.Groth16JobCallback)(C.goGroth16JobDone)
+	id := uint64(C.prove_async_bn254(c.ctx, inputC, C.uint64_t(timeoutMs), callback, nil))
+	if id == 0 {
+		return 0, nil, errors.New("libgroth16: invalid proving job or job queue full")
+	}
+	jobs[id] = result
+	return id, result, nil
+}
+
+// QueryJob returns whether a job is queued or running.
+func QueryJob(id uint64) JobStatus {
+	return JobStatus(C.job_status_bn254(C.uint64_t(id)))
+}
+
+// CancelJob stops a queued or running job, whose result is then ErrJobCancelled unless it completed first. It returns
+// false for an unknown job.
+func CancelJob(id uint64) bool {
+	return C.cancel_job_bn254(C.uint64_t(id)) == 0
+}
@@ -8,11 +8,28 @@ package go_groth16
 */
 import "C"
 import (
+	"context"
+	"fmt"
+	"time"
 	"unsafe"
 )
 
 const BufferSize = 4096
 
+// CheckLibrary reports an error if the linked libgroth16 implements another version of the C API than the header this
+// package was built with, as happens with a stale prebuilt library. Call it once at startup, before using the package.
+func CheckLibrary() error {
+	if version := C.groth16_abi_version(); version != C.GROTH16_ABI_VERSION {
+		return fmt.Errorf("libgroth16 %s has ABI version %d, expected %d", LibraryVersion(), version, C.GROTH16_ABI_VERSION)
+	}
+	return nil
+}
+
+// LibraryVersion returns the version of the linked libgroth16.
+func LibraryVersion() string {
+	return C.GoString(C.groth16_library_version())
+}
+
 func VerifyBn254(vk string, provingOutput string) bool {
 	vkC := C.CString(vk)
 	provingOutputC := C.CString(provingOutput)
@@ -21,7 +38,7 @@ func VerifyBn254(vk string, provingOutput string) bool {
 }
 
 type ProvingContext struct {
-	ctx    unsafe.Pointer
+	ctx    *C.ProvingContext
 	buffer unsafe.Pointer
 	output *string
 }
@@ -46,6 +63,79 @@ func (c *ProvingContext) Prove(input string) string {
 	return C.GoString((*C.char)(c.buffer))
 }
 
+// ProveContext is like Prove, and stops proving between its phases once ctx is done. It then returns ctx.Err(); the
+// proof may still run for the length of one phase, as witness calculation and the MSMs cannot be interrupted.
+func (c *ProvingContext) ProveContext(ctx context.Context, input string) (string, error) {
+	var timeoutMs int64
+	if deadline, ok := ctx.Deadline(); ok {
+		timeoutMs = time.Until(deadline).Milliseconds()
+		if timeoutMs < 1 {
+			return "", context.DeadlineExceeded
+		}
+	}
+	token := C.groth16_cancel_token_create(C.uint64_t(timeoutMs))
+	done := make(chan struct{})
+	watcher := make(chan struct{})
+	go func() {
+		defer close(watcher)
+		select {
+		case <-ctx.Done():
+			C.groth16_cancel_token_cancel(token)
+		case <-done:
+		}
+	}()
+	defer func() {
+		close(done)
+		<-watcher
+		C.groth16_cancel_token_destroy(token)
+	}()
+
+	inputC := C.CString(input)
+	defer C.free(unsafe.Pointer(inputC))
+	res := C.prove_cancellable_bn254(c.ctx, inputC, (*C.char)(c.buffer), BufferSize, token)
+	switch {
+	case res == -3:
+		return "", ctx.Err()
+	case res == -4:
+		return "", context.DeadlineExceeded
+	case res < 0:
+		return "", fmt.Errorf("failed to prove: %d", res)
+	}
+	return C.GoString((*C.char)(c.buffer)), nil
+}
+
+// ProvingStats reports where the time of one proof went, for metrics.
+type ProvingStats struct {
+	Parse       time.Duration // parsing and checking the input
+	Witness     time.Duration // witness calculation
+	Constraints time.Duration // constraint synthesis and check
+	Prove       time.Duration // Groth16 proving, mostly MSMs and FFTs
+	Total       time.Duration
+	WitnessLen  uint64
+	// PeakAllocation is the peak of bytes allocated while proving, 0 unless libgroth16 was built with alloc-stats.
+	PeakAllocation uint64
+}
+
+// ProveWithStats is like Prove, and also returns the duration of each proving phase.
+func (c *ProvingContext) ProveWithStats(input string) (string, ProvingStats, error) {
+	inputC := C.CString(input)
+	defer C.free(unsafe.Pointer(inputC))
+	var stats C.ProvingStats
+	res := C.prove_with_stats_bn254(c.ctx, inputC, (*C.char)(c.buffer), BufferSize, &stats)
+	if res < 0 {
+		return "", ProvingStats{}, fmt.Errorf("failed to prove: %d", res)
+	}
+	return C.GoString((*C.char)(c.buffer)), ProvingStats{
+		Parse:          time.Duration(stats.parse_us) * time.Microsecond,
+		Witness:        time.Duration(stats.witness_us) * time.Microsecond,
+		Constraints:    time.Duration(stats.constraints_us) * time.Microsecond,
+		Prove:          time.Duration(stats.prove_us) * time.Microsecond,
+		Total:          time.Duration(stats.total_us) * time.Microsecond,
+		WitnessLen:     uint64(stats.witness_len),
+		PeakAllocation: uint64(stats.peak_allocation),
+	}, nil
+}
+
 func (c *ProvingContext) VerifyingKey() string {
 	size := C.verifying_key_size_bn254(c.ctx)
 	tempBuffer := C.malloc(C.size_t(size + 1))
@@ -0,0 +1,70 @@
+// P/Invoke declarations for the handle-based API of libgroth16/src/handle.rs. The test_csharp_bindings test checks the
+// function names and status values against the library; keep the signatures in line with include/groth16.h.
+using System;
+using System.Runtime.InteropServices;
+
+namespace Groth16
+{
+    /// <summary>Bytes owned by the library, freed with <see cref="NativeMethods.groth16_buffer_release"/>.</summary>
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Groth16Buffer
+    {
+        public IntPtr Data;
+        public UIntPtr Len;
+
+        public string ReadString() =>
+            Data == IntPtr.Zero ? string.Empty : Marshal.PtrToStringUTF8(Data, checked((int)Len.ToUInt64()));
+    }
+
+    public sealed class Groth16ContextHandle : SafeHandle
+    {
+        public Groth16ContextHandle() : base(IntPtr.Zero, true) { }
+
+        public override bool IsInvalid => handle == IntPtr.Zero;
+
+        protected override bool ReleaseHandle()
+        {
+            NativeMethods.groth16_context_destroy(handle);
+            return true;
+        }
+    }
+
+    public enum Groth16Status
+    {
+        Ok = 0,
+        InvalidArtifact = 1,
+        InvalidInput = 2,
+        InvalidVerifyingKey = 3,
+        WitnessGeneration = 4,
+        UnsatisfiedConstraints = 5,
+        Proving = 6,
+        UnknownCircuit = 7,
+        Internal = 8,
+        UntrustedKey = 9,
+        Cancelled = 10,
+        TimedOut = 11,
+    }
+
+    internal static class NativeMethods
+    {
+        private const string Library = "groth16";
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern Groth16Status groth16_context_create(byte[] wasmPath, UIntPtr wasmPathLen, byte[] r1csPath, UIntPtr r1csPathLen, byte[] zkeyPath, UIntPtr zkeyPathLen, out Groth16ContextHandle ctx, out Groth16Buffer error);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void groth16_context_destroy(IntPtr ctx);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern Groth16Status groth16_context_verifying_key(Groth16ContextHandle ctx, out Groth16Buffer output, out Groth16Buffer error);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern Groth16Status groth16_context_prove(Groth16ContextHandle ctx, byte[] input, UIntPtr inputLen, out Groth16Buffer output, out Groth16Buffer error);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern Groth16Status groth16_verify(byte[] vk, UIntPtr vkLen, byte[] provingOutput, UIntPtr provingOutputLen, [MarshalAs(UnmanagedType.U1)] out bool valid, out Groth16Buffer error);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void groth16_buffer_release(Groth16Buffer buffer);
+    }
+}
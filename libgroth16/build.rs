@@ -0,0 +1,28 @@
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=UPDATE_BINDINGS");
+    if std::env::var_os("UPDATE_BINDINGS").is_some() {
+        update_header();
+    }
+    #[cfg(feature = "grpc")]
+    {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path().unwrap());
+        tonic_build::compile_protos("proto/groth16.proto").unwrap();
+    }
+}
+
+/// Regenerates `include/groth16.h` from the `extern "C"` functions of the crate. cbindgen runs `cargo metadata`, which
+/// offline and vendored builds may not be able to, so it only runs on request and a failure does not fail the build;
+/// `test_c_header` catches a stale header either way.
+fn update_header() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    match cbindgen::generate(&crate_dir) {
+        Ok(bindings) => {
+            bindings.write_to_file(Path::new(&crate_dir).join("include/groth16.h"));
+        }
+        Err(err) => println!("cargo:warning=failed to generate the C header: {}", err),
+    }
+}
@@ -1,11 +1,378 @@
-#ifndef _BLS_H_
-#define _BLS_H_
+#ifndef GROTH16_H
+#define GROTH16_H
 
-int groth16_verify_bn254(const char* vk, const char* proving_output);
-void* load_context_bn254(const char* wasm_path, const char* r1cs_path, const char* zkey_path);
-int verifying_key_size_bn254(const void* ctx);
-int export_verifying_key_bn254(const void* ctx, char* buf, int max_len);
-int prove_bn254(const void* ctx, const char* input, char* buf, int max_len);
-void free_context_bn254(void* ctx);
+/* Generated by build.rs with cbindgen, do not edit. */
 
-#endif
\ No newline at end of file
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Version of the C API, raised on every incompatible change to it. Hosts compare it with the `GROTH16_ABI_VERSION` of
+ * the header they were built against, to refuse a mismatched prebuilt library.
+ */
+#define GROTH16_ABI_VERSION 1
+
+#define GROTH16_OK 0
+
+#define GROTH16_INVALID_ARTIFACT 1
+
+#define GROTH16_INVALID_INPUT 2
+
+#define GROTH16_INVALID_VERIFYING_KEY 3
+
+#define GROTH16_WITNESS_GENERATION 4
+
+#define GROTH16_UNSATISFIED_CONSTRAINTS 5
+
+#define GROTH16_PROVING 6
+
+#define GROTH16_UNKNOWN_CIRCUIT 7
+
+#define GROTH16_INTERNAL 8
+
+#define GROTH16_UNTRUSTED_KEY 9
+
+#define GROTH16_CANCELLED 10
+
+#define GROTH16_TIMED_OUT 11
+
+#define GROTH16_JOB_UNKNOWN 0
+
+#define GROTH16_JOB_QUEUED 1
+
+#define GROTH16_JOB_RUNNING 2
+
+/**
+ * Stops a proof in flight, on request or at a deadline. Proving checks the token between its phases: before and after
+ * witness calculation, before `Groth16::prove`, and inside it once the constraints are synthesized, before the
+ * witness map FFT and the MSMs. Neither those nor the wasm witness calculator can be interrupted, so a proof may run
+ * on for the length of one of them after the token fired.
+ */
+typedef struct CancelToken CancelToken;
+
+typedef struct CircuitRegistry CircuitRegistry;
+
+/**
+ * A proving context behind a handle.
+ */
+typedef struct Groth16Context Groth16Context;
+
+typedef struct ProvingContext ProvingContext;
+
+/**
+ * Where the time of one proof went, filled in by `do_prove_with_stats`. Durations are in microseconds.
+ */
+typedef struct ProvingStats {
+  /**
+   * Parsing the input JSON and checking it against the circuit.
+   */
+  uint64_t parse_us;
+  /**
+   * Witness calculation in the circuit's wasm, including the wait for the witness lock.
+   */
+  uint64_t witness_us;
+  /**
+   * Synthesizing the constraints and checking the witness against them.
+   */
+  uint64_t constraints_us;
+  /**
+   * `Groth16::prove`, mostly the MSMs and FFTs.
+   */
+  uint64_t prove_us;
+  uint64_t total_us;
+  /**
+   * Number of field elements in the witness.
+   */
+  uint64_t witness_len;
+  /**
+   * Highest number of bytes allocated by the process while proving, above what was allocated when the proof started.
+   * Concurrent proofs share one high-water mark, so theirs are only indicative. Always 0 without the `alloc-stats`
+   * feature.
+   */
+  uint64_t peak_allocation;
+} ProvingStats;
+
+/**
+ * Bytes owned by the library. Empty buffers have a null `data`.
+ */
+typedef struct Groth16Buffer {
+  uint8_t *data;
+  size_t len;
+} Groth16Buffer;
+
+/**
+ * Called on a worker thread when a job finished, with its id, a status and `user_data`. The status is 0 with the
+ * proving output in `output`, otherwise the error message is in `output` and the status is -3 if the job was
+ * cancelled, -4 if it timed out, and -1 for any other failure, as `prove_cancellable_bn254` returns. `output` is only
+ * valid during the call.
+ */
+typedef void (*Groth16JobCallback)(uint64_t job_id, int status, const char *output, void *user_data);
+
+/**
+ * Receives a log level, 1 (error) to 5 (trace), and a NUL-terminated message that is only valid during the call. It
+ * may be called from any thread, also concurrently.
+ */
+typedef void (*Groth16LogCallback)(int level, const char *message);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+uint32_t groth16_abi_version(void);
+
+/**
+ * The crate version, as a static NUL-terminated string.
+ */
+const char *groth16_library_version(void);
+
+int groth16_verify_bn254(const char *vk, const char *proving_output);
+
+/**
+ * Like `groth16_verify_bn254`, and also checks the guardianhash pubkey against a JWKS document. For a valid proof the
+ * matching `kid` is written to `buf`. Returns -3 if the proof is valid but no key of the set matches.
+ */
+int groth16_verify_jwks_bn254(const char *vk,
+                              const char *proving_output,
+                              const char *jwks,
+                              char *buf,
+                              int max_len);
+
+/**
+ * Verifies a proving output whose proof is in the given encoding, see `ProofEncoding::from_code`; 0 auto-detects it.
+ */
+int groth16_verify_encoded_bn254(const char *vk,
+                                 const char *proving_output,
+                                 int encoding);
+
+int groth16_verify_named_bn254(const struct ProvingContext *ctx, const char *proving_output);
+
+struct ProvingContext *load_context_bn254(const char *wasm_path,
+                                          const char *r1cs_path,
+                                          const char *zkey_path);
+
+int verifying_key_size_bn254(const struct ProvingContext *ctx);
+
+int export_verifying_key_bn254(const struct ProvingContext *ctx, char *buf, int max_len);
+
+/**
+ * Writes the verifying key in a binary encoding, see `ProofEncoding::from_code`.
+ */
+int export_verifying_key_encoded_bn254(const struct ProvingContext *ctx,
+                                       int encoding,
+                                       char *buf,
+                                       int max_len);
+
+int prove_bn254(const struct ProvingContext *ctx, const char *input, char *buf, int max_len);
+
+/**
+ * Like `prove_bn254`, and also fills `stats` with the duration of each phase, when it is not NULL.
+ */
+int prove_with_stats_bn254(const struct ProvingContext *ctx,
+                           const char *input,
+                           char *buf,
+                           int max_len,
+                           struct ProvingStats *stats);
+
+/**
+ * Creates a token that stops `prove_cancellable_bn254` once `groth16_cancel_token_cancel` is called or, unless it is
+ * 0, `timeout_ms` has passed. Free it with `groth16_cancel_token_destroy` after the proof returned.
+ */
+struct CancelToken *groth16_cancel_token_create(uint64_t timeout_ms);
+
+/**
+ * Cancels the proof using `token`; it may be called from any thread while the proof runs.
+ */
+void groth16_cancel_token_cancel(const struct CancelToken *token);
+
+void groth16_cancel_token_destroy(struct CancelToken *token);
+
+/**
+ * Like `prove_bn254`, and stops between proving phases once `token` fires. Returns -3 when the token was cancelled,
+ * -4 when its deadline passed. `token` may be NULL. Witness calculation, the witness map FFT and the MSMs cannot be
+ * interrupted, so the call may return up to one of them after the token fired.
+ */
+int prove_cancellable_bn254(const struct ProvingContext *ctx,
+                            const char *input,
+                            char *buf,
+                            int max_len,
+                            const struct CancelToken *token);
+
+/**
+ * Like `prove_bn254`, with the proof in the given encoding, see `ProofEncoding::from_code`.
+ */
+int prove_encoded_bn254(const struct ProvingContext *ctx,
+                        const char *input,
+                        int encoding,
+                        char *buf,
+                        int max_len);
+
+int prove_named_bn254(const struct ProvingContext *ctx, const char *input, char *buf, int max_len);
+
+/**
+ * Writes the guardianhash circuit input JSON. `signature` may be NULL when `jwt` carries its signature part; `ctx` may
+ * be NULL, otherwise the JWT length is taken from the circuit when its symbols are loaded.
+ */
+int build_guardian_input_bn254(const struct ProvingContext *ctx,
+                               const char *jwt,
+                               const char *signature,
+                               const char *modulus,
+                               const char *salt,
+                               char *buf,
+                               int max_len);
+
+/**
+ * Writes the public outputs of a guardianhash proving output as a `GuardianStatement` JSON.
+ */
+int decode_guardian_output_bn254(const char *proving_output, char *buf, int max_len);
+
+/**
+ * Writes the public inputs of a `GuardianStatement` JSON as a JSON array of decimal strings.
+ */
+int encode_guardian_output_bn254(const char *statement, char *buf, int max_len);
+
+int load_symbols_bn254(const struct ProvingContext *ctx, const char *sym_path);
+
+int inspect_context_bn254(const struct ProvingContext *ctx, char *buf, int max_len);
+
+int input_schema_bn254(const struct ProvingContext *ctx, char *buf, int max_len);
+
+int reload_context_bn254(const struct ProvingContext *ctx,
+                         const char *wasm_path,
+                         const char *r1cs_path,
+                         const char *zkey_path,
+                         char *buf,
+                         int max_len);
+
+void free_context_bn254(struct ProvingContext *state);
+
+struct CircuitRegistry *create_registry_bn254(int lazy, uint64_t max_loaded_bytes);
+
+int register_circuits_bn254(const struct CircuitRegistry *registry, const char *manifest_path);
+
+int prove_circuit_bn254(const struct CircuitRegistry *registry,
+                        const char *circuit_id,
+                        const char *input,
+                        char *buf,
+                        int max_len);
+
+int list_circuits_bn254(const struct CircuitRegistry *registry, char *buf, int max_len);
+
+int unload_circuit_bn254(const struct CircuitRegistry *registry, const char *circuit_id);
+
+void free_registry_bn254(struct CircuitRegistry *registry);
+
+/**
+ * Writes a number, decimal or `0x` prefixed hex, as a JSON array of `n_limbs` decimal limbs of `limb_bits` bits, least
+ * significant first.
+ */
+int groth16_encode_limbs(const char *value,
+                         int limb_bits,
+                         int n_limbs,
+                         char *buf,
+                         int max_len);
+
+/**
+ * Writes the decimal number joined from a JSON array of decimal limbs, least significant first.
+ */
+int groth16_decode_limbs(const char *limbs, int limb_bits, char *buf, int max_len);
+
+/**
+ * Writes the UTF-8 bytes of a string as a JSON array of decimal strings, zero-padded to `len`.
+ */
+int groth16_encode_padded_bytes(const char *value, int len, char *buf, int max_len);
+
+/**
+ * Writes base64url, such as a JWK `n`, as hex.
+ */
+int groth16_base64url_to_hex(const char *value, char *buf, int max_len);
+
+/**
+ * Loads a circuit and stores its handle in `ctx`. The paths are UTF-8 byte spans.
+ */
+int32_t groth16_context_create(const uint8_t *wasm_path,
+                               size_t wasm_path_len,
+                               const uint8_t *r1cs_path,
+                               size_t r1cs_path_len,
+                               const uint8_t *zkey_path,
+                               size_t zkey_path_len,
+                               struct Groth16Context **ctx,
+                               struct Groth16Buffer *error);
+
+/**
+ * Frees a context. Null is ignored.
+ */
+void groth16_context_destroy(struct Groth16Context *ctx);
+
+/**
+ * Stores the verifying key of a context, in compressed hex, in `output`.
+ */
+int32_t groth16_context_verifying_key(const struct Groth16Context *ctx,
+                                      struct Groth16Buffer *output,
+                                      struct Groth16Buffer *error);
+
+/**
+ * Proves a circuit input JSON and stores the proving output JSON in `output`.
+ */
+int32_t groth16_context_prove(const struct Groth16Context *ctx,
+                              const uint8_t *input,
+                              size_t input_len,
+                              struct Groth16Buffer *output,
+                              struct Groth16Buffer *error);
+
+/**
+ * Verifies a proving output JSON against a verifying key and stores the result in `valid`.
+ */
+int32_t groth16_verify(const uint8_t *vk,
+                       size_t vk_len,
+                       const uint8_t *proving_output,
+                       size_t proving_output_len,
+                       bool *valid,
+                       struct Groth16Buffer *error);
+
+/**
+ * Frees a buffer returned by the library. Empty buffers are ignored.
+ */
+void groth16_buffer_release(struct Groth16Buffer buffer);
+
+/**
+ * Sets the number of worker threads, 2 by default. Returns -1 once the first job was submitted, or for 0 workers.
+ */
+int groth16_set_worker_count(size_t count);
+
+/**
+ * Queues a proof of `input` and returns its job id, or 0 if an argument is invalid or 64 jobs are already waiting for
+ * a worker. Unless it is 0, the job times out `timeout_ms` after it was queued, waiting in the queue included.
+ * `callback` is called exactly once for every job that was queued.
+ */
+uint64_t prove_async_bn254(const struct ProvingContext *ctx,
+                           const char *input,
+                           uint64_t timeout_ms,
+                           Groth16JobCallback callback,
+                           void *user_data);
+
+/**
+ * Returns `GROTH16_JOB_QUEUED`, `GROTH16_JOB_RUNNING` until the job's callback returned, or `GROTH16_JOB_UNKNOWN`.
+ */
+int job_status_bn254(uint64_t job_id);
+
+/**
+ * Cancels a job: a queued job is skipped and a running one stops between its phases, see `prove_cancellable_bn254`.
+ * The callback is still called, with status -3 unless the proof completed first. Returns -1 if the job is unknown.
+ */
+int cancel_job_bn254(uint64_t job_id);
+
+/**
+ * Sends the library's log records up to `level` (0 off, 1 error, 2 warn, 3 info, 4 debug, 5 trace) to `callback`, or
+ * stops logging if it is NULL. Returns -1 for an invalid level, or if the process already has another Rust logger.
+ */
+int groth16_set_log_callback(int level,
+                             Groth16LogCallback callback);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* GROTH16_H */
@@ -0,0 +1,42 @@
+package io.portkey.groth16;
+
+/** An error of the native library, classified by its {@link Cause}. */
+public class Groth16Exception extends RuntimeException {
+    /** Mirrors the library's {@code FailureCause}. */
+    public enum Cause {
+        /** The wasm, r1cs or zkey file could not be read. */
+        INVALID_ARTIFACT,
+        /** The circuit input or the proving output could not be parsed. */
+        INVALID_INPUT,
+        /** The verifying key could not be decoded. */
+        INVALID_VERIFYING_KEY,
+        /** The witness calculator rejected the input. */
+        WITNESS_GENERATION,
+        /** The witness does not satisfy the circuit constraints. */
+        UNSATISFIED_CONSTRAINTS,
+        /** Constraint synthesis or proof generation failed. */
+        PROVING,
+        /** No circuit is registered under the requested id. */
+        UNKNOWN_CIRCUIT,
+        /** The proof is valid but its issuer key is not in the trusted key set. */
+        UNTRUSTED_KEY,
+        /** Any other failure, including a panic in native code. */
+        INTERNAL,
+        /** Proving was stopped through its cancel token. */
+        CANCELLED,
+        /** Proving ran past the deadline of its cancel token. */
+        TIMED_OUT,
+    }
+
+    private final Cause cause;
+
+    /** Called from native code with the name of a {@link Cause} constant. */
+    Groth16Exception(String cause, String message) {
+        super(message);
+        this.cause = Cause.valueOf(cause);
+    }
+
+    public Cause getFailureCause() {
+        return cause;
+    }
+}
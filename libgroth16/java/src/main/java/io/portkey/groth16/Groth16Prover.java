@@ -0,0 +1,68 @@
+package io.portkey.groth16;
+
+import java.util.concurrent.locks.ReadWriteLock;
+import java.util.concurrent.locks.ReentrantReadWriteLock;
+
+/**
+ * A loaded circuit. Proofs may be generated from several threads at once; {@link #close()} waits for them to finish
+ * before freeing the native context.
+ */
+public final class Groth16Prover implements AutoCloseable {
+    static {
+        System.loadLibrary("groth16");
+    }
+
+    private final ReadWriteLock lock = new ReentrantReadWriteLock();
+    private long handle;
+
+    /** Loads the circuit artifacts, which takes a few seconds. */
+    public Groth16Prover(String wasmPath, String r1csPath, String zkeyPath) {
+        handle = load(wasmPath, r1csPath, zkeyPath);
+    }
+
+    /** Generates a proof for a circuit input JSON and returns the proving output JSON. */
+    public String prove(String input) {
+        lock.readLock().lock();
+        try {
+            return prove(checkOpen(), input);
+        } finally {
+            lock.readLock().unlock();
+        }
+    }
+
+    /** The verifying key, in compressed hex. */
+    public String verifyingKey() {
+        lock.readLock().lock();
+        try {
+            return verifyingKey(checkOpen());
+        } finally {
+            lock.readLock().unlock();
+        }
+    }
+
+    @Override
+    public void close() {
+        lock.writeLock().lock();
+        try {
+            free(handle);
+            handle = 0;
+        } finally {
+            lock.writeLock().unlock();
+        }
+    }
+
+    private long checkOpen() {
+        if (handle == 0) {
+            throw new IllegalStateException("prover is closed");
+        }
+        return handle;
+    }
+
+    private static native long load(String wasmPath, String r1csPath, String zkeyPath);
+
+    private static native String prove(long handle, String input);
+
+    private static native String verifyingKey(long handle);
+
+    private static native void free(long handle);
+}
@@ -0,0 +1,16 @@
+package io.portkey.groth16;
+
+public final class Groth16Verifier {
+    static {
+        System.loadLibrary("groth16");
+    }
+
+    private Groth16Verifier() {}
+
+    /**
+     * Verifies a proving output JSON against a verifying key, as returned by {@link Groth16Prover#verifyingKey()}.
+     *
+     * @throws Groth16Exception if the verifying key or the proving output cannot be decoded
+     */
+    public static native boolean verify(String vk, String provingOutput);
+}
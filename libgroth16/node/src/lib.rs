@@ -0,0 +1,114 @@
+use groth16::{do_prove, do_verify, failure_cause, load_context, serialize, FailureCause};
+use napi::bindgen_prelude::*;
+use napi::{Env, Task};
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+fn to_napi_error(err: anyhow::Error) -> Error {
+    let status = match failure_cause(&err) {
+        FailureCause::InvalidInput | FailureCause::InvalidVerifyingKey => Status::InvalidArg,
+        _ => Status::GenericFailure,
+    };
+    Error::new(status, format!("{:#}", err))
+}
+
+/// The rapidsnark / snarkjs proof, as in the library's `ProvingOutput`.
+#[napi(object)]
+#[derive(Serialize, Deserialize)]
+pub struct Proof {
+    #[napi(js_name = "pi_a")]
+    pub pi_a: Vec<String>,
+    #[napi(js_name = "pi_b")]
+    pub pi_b: Vec<Vec<String>>,
+    #[napi(js_name = "pi_c")]
+    pub pi_c: Vec<String>,
+    pub protocol: String,
+}
+
+/// Field names are kept in snake case, so that the object is the same JSON the other bindings produce.
+#[napi(object)]
+#[derive(Serialize, Deserialize)]
+pub struct ProvingOutput {
+    #[napi(js_name = "public_inputs")]
+    pub public_inputs: Vec<String>,
+    pub proof: Proof,
+}
+
+#[napi]
+pub struct ProvingContext {
+    ctx: Arc<groth16::ProvingContext>,
+}
+
+#[napi]
+impl ProvingContext {
+    /// Generates a proof for the circuit input JSON on the libuv thread pool.
+    #[napi(ts_return_type = "Promise<ProvingOutput>")]
+    pub fn prove(&self, input: String) -> AsyncTask<ProveTask> {
+        AsyncTask::new(ProveTask {
+            ctx: self.ctx.clone(),
+            input,
+        })
+    }
+
+    /// The verifying key in compressed hex.
+    #[napi]
+    pub fn verifying_key(&self) -> String {
+        self.ctx.verifying_key_in_hex()
+    }
+}
+
+pub struct LoadTask {
+    wasm: String,
+    r1cs: String,
+    zkey: String,
+}
+
+impl Task for LoadTask {
+    type Output = groth16::ProvingContext;
+    type JsValue = ProvingContext;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        load_context(&self.wasm, &self.r1cs, &self.zkey).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, ctx: Self::Output) -> Result<Self::JsValue> {
+        Ok(ProvingContext { ctx: Arc::new(ctx) })
+    }
+}
+
+pub struct ProveTask {
+    ctx: Arc<groth16::ProvingContext>,
+    input: String,
+}
+
+impl Task for ProveTask {
+    type Output = String;
+    type JsValue = ProvingOutput;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let (pub_inputs, proof) = do_prove(&self.ctx, &self.input).map_err(to_napi_error)?;
+        serialize(pub_inputs, proof).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        serde_json::from_str(&output).map_err(|e| to_napi_error(e.into()))
+    }
+}
+
+/// Loads the circuit artifacts on the libuv thread pool, as compiling the wasm takes a while.
+#[napi(js_name = "loadContext", ts_return_type = "Promise<ProvingContext>")]
+pub fn load(wasm: String, r1cs: String, zkey: String) -> AsyncTask<LoadTask> {
+    AsyncTask::new(LoadTask { wasm, r1cs, zkey })
+}
+
+/// Verifies a proving output, as an object or a JSON string, against a verifying key. Verification takes a few
+/// milliseconds, so it runs on the calling thread.
+#[napi]
+pub fn verify(vk: String, output: Either<String, ProvingOutput>) -> Result<bool> {
+    let output = match output {
+        Either::A(output) => output,
+        Either::B(output) => serde_json::to_string(&output).map_err(|e| to_napi_error(e.into()))?,
+    };
+    do_verify(&vk, &output).map_err(to_napi_error)
+}
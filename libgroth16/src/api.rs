@@ -1,8 +1,39 @@
+//! The C API over NUL-terminated strings and caller-provided buffers, declared in `include/groth16.h`.
+//!
+//! All functions share one safety contract: strings are null or NUL-terminated, buffers are valid for their length, and
+//! contexts, registries and cancel tokens come from this library and are freed once.
+#![allow(clippy::missing_safety_doc)]
+use crate::cancel::CancelToken;
+use crate::dto::ProvingOutput;
+use crate::encoding;
+use crate::guardian::{verify_guardian_proof, GuardianInputBuilder, GuardianStatement};
+use crate::proof::ProofEncoding;
+use crate::registry::{CircuitRegistry, RegistryConfig};
+use crate::stats::ProvingStats;
 use crate::utils::{
-    do_prove, do_verify, load_context, ret_or_err, serialize, write_to_buffer, ProvingContext,
+    do_prove, do_prove_cancellable, do_prove_with_stats, do_verify, do_verify_encoded,
+    do_verify_named, failure_cause, load_context, ret_or_err, serialize, serialize_encoded,
+    serialize_named, write_to_buffer, FailureCause, ProvingContext,
 };
+use num_bigint::BigUint;
+use num_traits::Num;
 use std::ffi::CStr;
 
+/// Version of the C API, raised on every incompatible change to it. Hosts compare it with the `GROTH16_ABI_VERSION` of
+/// the header they were built against, to refuse a mismatched prebuilt library.
+pub const GROTH16_ABI_VERSION: u32 = 1;
+
+#[no_mangle]
+pub extern "C" fn groth16_abi_version() -> u32 {
+    GROTH16_ABI_VERSION
+}
+
+/// The crate version, as a static NUL-terminated string.
+#[no_mangle]
+pub extern "C" fn groth16_library_version() -> *const cty::c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const cty::c_char
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn groth16_verify_bn254(
     vk: *const cty::c_char,
@@ -15,7 +46,89 @@ pub unsafe extern "C" fn groth16_verify_bn254(
             Ok(true) => 1,
             Ok(false) => 0,
             Err(err) => {
-                println!("{}", err);
+                log::warn!("failed to verify proof: {:#}", err);
+                -2
+            }
+        },
+        _ => -1,
+    }
+}
+
+/// Like `groth16_verify_bn254`, and also checks the guardianhash pubkey against a JWKS document. For a valid proof the
+/// matching `kid` is written to `buf`. Returns -3 if the proof is valid but no key of the set matches.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_verify_jwks_bn254(
+    vk: *const cty::c_char,
+    proving_output: *const cty::c_char,
+    jwks: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let vk = unsafe { CStr::from_ptr(vk).to_str() };
+    let proving_output = unsafe { CStr::from_ptr(proving_output).to_str() };
+    let jwks = unsafe { CStr::from_ptr(jwks).to_str() };
+    match (vk, proving_output, jwks) {
+        (Ok(vk), Ok(proving_output), Ok(jwks)) => {
+            match verify_guardian_proof(vk, proving_output, jwks) {
+                Ok(verification) => match verification.kid {
+                    Some(kid) => match write_to_buffer(&kid, buf, max_len) {
+                        len if len < 0 => len,
+                        _ => 1,
+                    },
+                    None => 0,
+                },
+                Err(err) if failure_cause(&err) == FailureCause::UntrustedKey => -3,
+                Err(err) => {
+                    log::warn!("failed to verify proof: {:#}", err);
+                    -2
+                }
+            }
+        }
+        _ => -1,
+    }
+}
+
+/// Verifies a proving output whose proof is in the given encoding, see `ProofEncoding::from_code`; 0 auto-detects it.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_verify_encoded_bn254(
+    vk: *const cty::c_char,
+    proving_output: *const cty::c_char,
+    encoding: cty::c_int,
+) -> cty::c_int {
+    let vk = unsafe { CStr::from_ptr(vk).to_str() };
+    let proving_output = unsafe { CStr::from_ptr(proving_output).to_str() };
+    let encoding = match encoding {
+        0 => None,
+        code => match ProofEncoding::from_code(code) {
+            Some(encoding) => Some(encoding),
+            None => return -1,
+        },
+    };
+    match (vk, proving_output) {
+        (Ok(vk), Ok(proving_output)) => match do_verify_encoded(vk, proving_output, encoding) {
+            Ok(true) => 1,
+            Ok(false) => 0,
+            Err(err) => {
+                log::warn!("failed to verify proof: {:#}", err);
+                -2
+            }
+        },
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn groth16_verify_named_bn254(
+    ctx: Option<&ProvingContext>,
+    proving_output: *const cty::c_char,
+) -> cty::c_int {
+    let proving_output = unsafe { CStr::from_ptr(proving_output).to_str() };
+    match (ctx, proving_output) {
+        (Some(ctx), Ok(proving_output)) => match do_verify_named(ctx, proving_output) {
+            Ok(true) => 1,
+            Ok(false) => 0,
+            Err(err) => {
+                log::warn!("failed to verify proof: {:#}", err);
                 -2
             }
         },
@@ -41,7 +154,7 @@ pub unsafe extern "C" fn load_context_bn254(
 }
 
 #[no_mangle]
-pub extern "C" fn verifying_key_size_bn254(ctx: Option<&mut ProvingContext>) -> cty::c_int {
+pub extern "C" fn verifying_key_size_bn254(ctx: Option<&ProvingContext>) -> cty::c_int {
     match ctx {
         Some(ctx) => {
             let vk = ctx.verifying_key_in_hex();
@@ -53,7 +166,7 @@ pub extern "C" fn verifying_key_size_bn254(ctx: Option<&mut ProvingContext>) ->
 
 #[no_mangle]
 pub extern "C" fn export_verifying_key_bn254(
-    ctx: Option<&mut ProvingContext>,
+    ctx: Option<&ProvingContext>,
     buf: *mut cty::c_char,
     max_len: cty::c_int,
 ) -> cty::c_int {
@@ -66,9 +179,26 @@ pub extern "C" fn export_verifying_key_bn254(
     }
 }
 
+/// Writes the verifying key in a binary encoding, see `ProofEncoding::from_code`.
+#[no_mangle]
+pub extern "C" fn export_verifying_key_encoded_bn254(
+    ctx: Option<&ProvingContext>,
+    encoding: cty::c_int,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    match (ctx, ProofEncoding::from_code(encoding)) {
+        (Some(ctx), Some(encoding)) => match ctx.verifying_key_encoded(encoding) {
+            Ok(vk) => write_to_buffer(&vk, buf, max_len),
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn prove_bn254(
-    ctx: Option<&mut ProvingContext>,
+    ctx: Option<&ProvingContext>,
     input: *const cty::c_char,
     buf: *mut cty::c_char,
     max_len: cty::c_int,
@@ -86,8 +216,449 @@ pub unsafe extern "C" fn prove_bn254(
     }
 }
 
+/// Like `prove_bn254`, and also fills `stats` with the duration of each phase, when it is not NULL.
+#[no_mangle]
+pub unsafe extern "C" fn prove_with_stats_bn254(
+    ctx: Option<&ProvingContext>,
+    input: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+    stats: Option<&mut ProvingStats>,
+) -> cty::c_int {
+    let input = unsafe { CStr::from_ptr(input).to_str() };
+    match (ctx, input) {
+        (Some(ctx), Ok(input)) => match do_prove_with_stats(ctx, input) {
+            Ok((pub_inputs, proof, proving_stats)) => {
+                if let Some(stats) = stats {
+                    *stats = proving_stats;
+                }
+                match serialize(pub_inputs, proof) {
+                    Ok(output) => write_to_buffer(&output, buf, max_len),
+                    Err(_) => -1,
+                }
+            }
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+/// Creates a token that stops `prove_cancellable_bn254` once `groth16_cancel_token_cancel` is called or, unless it is
+/// 0, `timeout_ms` has passed. Free it with `groth16_cancel_token_destroy` after the proof returned.
+#[no_mangle]
+pub extern "C" fn groth16_cancel_token_create(timeout_ms: u64) -> *mut CancelToken {
+    Box::into_raw(Box::new(CancelToken::with_timeout_ms(timeout_ms)))
+}
+
+/// Cancels the proof using `token`; it may be called from any thread while the proof runs.
+#[no_mangle]
+pub extern "C" fn groth16_cancel_token_cancel(token: Option<&CancelToken>) {
+    if let Some(token) = token {
+        token.cancel();
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn groth16_cancel_token_destroy(token: *mut CancelToken) {
+    if !token.is_null() {
+        drop(Box::from_raw(token));
+    }
+}
+
+/// Like `prove_bn254`, and stops between proving phases once `token` fires. Returns -3 when the token was cancelled,
+/// -4 when its deadline passed. `token` may be NULL. Witness calculation, the witness map FFT and the MSMs cannot be
+/// interrupted, so the call may return up to one of them after the token fired.
+#[no_mangle]
+pub unsafe extern "C" fn prove_cancellable_bn254(
+    ctx: Option<&ProvingContext>,
+    input: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+    token: Option<&CancelToken>,
+) -> cty::c_int {
+    let input = unsafe { CStr::from_ptr(input).to_str() };
+    let never = CancelToken::new();
+    match (ctx, input) {
+        (Some(ctx), Ok(input)) => match do_prove_cancellable(ctx, input, token.unwrap_or(&never)) {
+            Ok((pub_inputs, proof)) => match serialize(pub_inputs, proof) {
+                Ok(output) => write_to_buffer(&output, buf, max_len),
+                Err(_) => -1,
+            },
+            Err(err) => match failure_cause(&err) {
+                FailureCause::Cancelled => -3,
+                FailureCause::TimedOut => -4,
+                _ => -1,
+            },
+        },
+        _ => -1,
+    }
+}
+
+/// Like `prove_bn254`, with the proof in the given encoding, see `ProofEncoding::from_code`.
+#[no_mangle]
+pub unsafe extern "C" fn prove_encoded_bn254(
+    ctx: Option<&ProvingContext>,
+    input: *const cty::c_char,
+    encoding: cty::c_int,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let input = unsafe { CStr::from_ptr(input).to_str() };
+    match (ctx, input, ProofEncoding::from_code(encoding)) {
+        (Some(ctx), Ok(input), Some(encoding)) => match do_prove(ctx, input) {
+            Ok((pub_inputs, proof)) => match serialize_encoded(pub_inputs, proof, encoding) {
+                Ok(output) => write_to_buffer(&output, buf, max_len),
+                Err(_) => -1,
+            },
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn prove_named_bn254(
+    ctx: Option<&ProvingContext>,
+    input: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let input = unsafe { CStr::from_ptr(input).to_str() };
+    match (ctx, input) {
+        (Some(ctx), Ok(input)) => match do_prove(ctx, input) {
+            Ok((pub_inputs, proof)) => match serialize_named(ctx, pub_inputs, proof) {
+                Ok(output) => write_to_buffer(&output, buf, max_len),
+                Err(_) => -1,
+            },
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+/// Writes the guardianhash circuit input JSON. `signature` may be NULL when `jwt` carries its signature part; `ctx` may
+/// be NULL, otherwise the JWT length is taken from the circuit when its symbols are loaded.
+#[no_mangle]
+pub unsafe extern "C" fn build_guardian_input_bn254(
+    ctx: Option<&ProvingContext>,
+    jwt: *const cty::c_char,
+    signature: *const cty::c_char,
+    modulus: *const cty::c_char,
+    salt: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let jwt = unsafe { CStr::from_ptr(jwt).to_str() };
+    let signature = if signature.is_null() {
+        Ok(None)
+    } else {
+        unsafe { CStr::from_ptr(signature).to_str() }.map(Some)
+    };
+    let modulus = unsafe { CStr::from_ptr(modulus).to_str() };
+    let salt = unsafe { CStr::from_ptr(salt).to_str() };
+    match (jwt, signature, modulus, salt) {
+        (Ok(jwt), Ok(signature), Ok(modulus), Ok(salt)) => {
+            let mut builder = GuardianInputBuilder::new(jwt, modulus, salt);
+            if let Some(signature) = signature {
+                builder = builder.signature(signature);
+            }
+            if let Some(ctx) = ctx {
+                builder = builder.for_context(ctx);
+            }
+            match builder.build() {
+                Ok(input) => write_to_buffer(&input.to_json(), buf, max_len),
+                Err(_) => -1,
+            }
+        }
+        _ => -1,
+    }
+}
+
+/// Writes the public outputs of a guardianhash proving output as a `GuardianStatement` JSON.
+#[no_mangle]
+pub unsafe extern "C" fn decode_guardian_output_bn254(
+    proving_output: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let proving_output = unsafe { CStr::from_ptr(proving_output).to_str() };
+    let statement = proving_output
+        .ok()
+        .and_then(|proving_output| serde_json::from_str::<ProvingOutput>(proving_output).ok())
+        .and_then(|proving_output| GuardianStatement::decode(&proving_output.public_inputs).ok());
+    match statement.map(|statement| serde_json::to_string(&statement)) {
+        Some(Ok(output)) => write_to_buffer(&output, buf, max_len),
+        _ => -1,
+    }
+}
+
+/// Writes the public inputs of a `GuardianStatement` JSON as a JSON array of decimal strings.
+#[no_mangle]
+pub unsafe extern "C" fn encode_guardian_output_bn254(
+    statement: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let statement = unsafe { CStr::from_ptr(statement).to_str() };
+    let public_inputs = statement
+        .ok()
+        .and_then(|statement| serde_json::from_str::<GuardianStatement>(statement).ok())
+        .and_then(|statement| statement.encode().ok());
+    match public_inputs.map(|public_inputs| serde_json::to_string(&public_inputs)) {
+        Some(Ok(output)) => write_to_buffer(&output, buf, max_len),
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn load_symbols_bn254(
+    ctx: Option<&ProvingContext>,
+    sym_path: *const cty::c_char,
+) -> cty::c_int {
+    let sym_path = unsafe { CStr::from_ptr(sym_path).to_str() };
+    match (ctx, sym_path) {
+        (Some(ctx), Ok(sym_path)) => match ctx.load_symbols(sym_path) {
+            Ok(()) => 0,
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn inspect_context_bn254(
+    ctx: Option<&ProvingContext>,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    match ctx {
+        Some(ctx) => match serde_json::to_string(&ctx.inspect()) {
+            Ok(output) => write_to_buffer(&output, buf, max_len),
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn input_schema_bn254(
+    ctx: Option<&ProvingContext>,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    match ctx.map(|ctx| ctx.input_schema()) {
+        Some(Ok(schema)) => match serde_json::to_string(&schema) {
+            Ok(output) => write_to_buffer(&output, buf, max_len),
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn reload_context_bn254(
+    ctx: Option<&ProvingContext>,
+    wasm_path: *const cty::c_char,
+    r1cs_path: *const cty::c_char,
+    zkey_path: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let wasm_path = unsafe { CStr::from_ptr(wasm_path).to_str() };
+    let r1cs_path = unsafe { CStr::from_ptr(r1cs_path).to_str() };
+    let zkey_path = unsafe { CStr::from_ptr(zkey_path).to_str() };
+    match (ctx, wasm_path, r1cs_path, zkey_path) {
+        (Some(ctx), Ok(wasm_path), Ok(r1cs_path), Ok(zkey_path)) => {
+            match ctx.reload(wasm_path, r1cs_path, zkey_path) {
+                Ok(rotation) => match serde_json::to_string(&rotation) {
+                    Ok(output) => write_to_buffer(&output, buf, max_len),
+                    Err(_) => -1,
+                },
+                Err(_) => -1,
+            }
+        }
+        _ => -1,
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn free_context_bn254(state: *mut ProvingContext) {
     assert!(!state.is_null());
     let _ = Box::from_raw(state); // Rust auto-drops it
 }
+
+#[no_mangle]
+pub extern "C" fn create_registry_bn254(
+    lazy: cty::c_int,
+    max_loaded_bytes: u64,
+) -> *mut CircuitRegistry {
+    Box::into_raw(Box::new(CircuitRegistry::new(RegistryConfig {
+        lazy: lazy != 0,
+        max_loaded_bytes,
+    })))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn register_circuits_bn254(
+    registry: Option<&CircuitRegistry>,
+    manifest_path: *const cty::c_char,
+) -> cty::c_int {
+    let manifest_path = unsafe { CStr::from_ptr(manifest_path).to_str() };
+    match (registry, manifest_path) {
+        (Some(registry), Ok(manifest_path)) => match registry.register_manifest(manifest_path) {
+            Ok(ids) => ids.len() as cty::c_int,
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn prove_circuit_bn254(
+    registry: Option<&CircuitRegistry>,
+    circuit_id: *const cty::c_char,
+    input: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let circuit_id = unsafe { CStr::from_ptr(circuit_id).to_str() };
+    let input = unsafe { CStr::from_ptr(input).to_str() };
+    match (registry, circuit_id, input) {
+        (Some(registry), Ok(circuit_id), Ok(input)) => match registry.prove(circuit_id, input) {
+            Ok(output) => write_to_buffer(&output, buf, max_len),
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn list_circuits_bn254(
+    registry: Option<&CircuitRegistry>,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    match registry {
+        Some(registry) => match serde_json::to_string(&registry.list()) {
+            Ok(output) => write_to_buffer(&output, buf, max_len),
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn unload_circuit_bn254(
+    registry: Option<&CircuitRegistry>,
+    circuit_id: *const cty::c_char,
+) -> cty::c_int {
+    let circuit_id = unsafe { CStr::from_ptr(circuit_id).to_str() };
+    match (registry, circuit_id) {
+        (Some(registry), Ok(circuit_id)) => registry.unload(circuit_id) as cty::c_int,
+        _ => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn free_registry_bn254(registry: *mut CircuitRegistry) {
+    assert!(!registry.is_null());
+    let _ = Box::from_raw(registry);
+}
+
+/// Writes a number, decimal or `0x` prefixed hex, as a JSON array of `n_limbs` decimal limbs of `limb_bits` bits, least
+/// significant first.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_encode_limbs(
+    value: *const cty::c_char,
+    limb_bits: cty::c_int,
+    n_limbs: cty::c_int,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let value = unsafe { CStr::from_ptr(value).to_str() };
+    let value = value.ok().and_then(|value| match value.strip_prefix("0x") {
+        Some(hex) => BigUint::from_str_radix(hex, 16).ok(),
+        None => BigUint::from_str_radix(value, 10).ok(),
+    });
+    let limbs = match (value, usize::try_from(limb_bits), usize::try_from(n_limbs)) {
+        (Some(value), Ok(limb_bits), Ok(n_limbs)) => encoding::to_limbs(&value, limb_bits, n_limbs),
+        _ => return -1,
+    };
+    match limbs.map(|limbs| serde_json::to_string(&limbs)) {
+        Ok(Ok(output)) => write_to_buffer(&output, buf, max_len),
+        _ => -1,
+    }
+}
+
+/// Writes the decimal number joined from a JSON array of decimal limbs, least significant first.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_decode_limbs(
+    limbs: *const cty::c_char,
+    limb_bits: cty::c_int,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let limbs = unsafe { CStr::from_ptr(limbs).to_str() };
+    let limbs = limbs
+        .ok()
+        .and_then(|limbs| serde_json::from_str::<Vec<String>>(limbs).ok());
+    match (limbs, usize::try_from(limb_bits)) {
+        (Some(limbs), Ok(limb_bits)) => match encoding::from_limbs(&limbs, limb_bits) {
+            Ok(value) => write_to_buffer(&value.to_string(), buf, max_len),
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+/// Writes the UTF-8 bytes of a string as a JSON array of decimal strings, zero-padded to `len`.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_encode_padded_bytes(
+    value: *const cty::c_char,
+    len: cty::c_int,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let value = unsafe { CStr::from_ptr(value).to_str() };
+    match (value, usize::try_from(len)) {
+        (Ok(value), Ok(len)) => {
+            match encoding::to_padded_bytes(value, len).map(|bytes| serde_json::to_string(&bytes)) {
+                Ok(Ok(output)) => write_to_buffer(&output, buf, max_len),
+                _ => -1,
+            }
+        }
+        _ => -1,
+    }
+}
+
+/// Writes base64url, such as a JWK `n`, as hex.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_base64url_to_hex(
+    value: *const cty::c_char,
+    buf: *mut cty::c_char,
+    max_len: cty::c_int,
+) -> cty::c_int {
+    let value = unsafe { CStr::from_ptr(value).to_str() };
+    match value.map(encoding::decode_base64url) {
+        Ok(Ok(bytes)) => write_to_buffer(&hex::encode(bytes), buf, max_len),
+        _ => -1,
+    }
+}
+
+#[cfg(test)]
+mod api_test {
+    #[test]
+    fn test_c_header() {
+        let mut generated = Vec::new();
+        cbindgen::generate(env!("CARGO_MANIFEST_DIR"))
+            .unwrap()
+            .write(&mut generated);
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/include/groth16.h");
+        let checked_in = std::fs::read(path).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "{} is out of date, regenerate it with make header",
+            path
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! The circuit arguments and loading that `groth16-server` and `groth16-grpc` share.
+use anyhow::Context;
+use groth16::{load_context, ProvingContext};
+use std::collections::HashMap;
+use std::thread;
+
+#[derive(clap::Args)]
+pub struct ServeArgs {
+    /// Circuit to serve, as NAME=WASM,R1CS,ZKEY (repeatable)
+    #[arg(long = "circuit", required = true, value_parser = parse_circuit)]
+    circuits: Vec<CircuitArgs>,
+    /// Maximum number of proofs generated at the same time, defaults to the number of CPUs
+    #[arg(long)]
+    max_concurrent_proofs: Option<usize>,
+}
+
+#[derive(Clone)]
+struct CircuitArgs {
+    name: String,
+    wasm: String,
+    r1cs: String,
+    zkey: String,
+}
+
+fn parse_circuit(value: &str) -> Result<CircuitArgs, String> {
+    let (name, paths) = value
+        .split_once('=')
+        .ok_or_else(|| "expected NAME=WASM,R1CS,ZKEY".to_string())?;
+    match paths.split(',').collect::<Vec<_>>()[..] {
+        [wasm, r1cs, zkey] => Ok(CircuitArgs {
+            name: name.to_string(),
+            wasm: wasm.to_string(),
+            r1cs: r1cs.to_string(),
+            zkey: zkey.to_string(),
+        }),
+        _ => Err("expected NAME=WASM,R1CS,ZKEY".to_string()),
+    }
+}
+
+pub struct Circuit {
+    pub ctx: ProvingContext,
+    /// The verifying key in compressed hex.
+    pub vk: String,
+}
+
+impl ServeArgs {
+    /// Loads every circuit, keyed by its name.
+    pub fn load_circuits(&self) -> anyhow::Result<HashMap<String, Circuit>> {
+        let mut circuits = HashMap::new();
+        for circuit in &self.circuits {
+            let ctx = load_context(&circuit.wasm, &circuit.r1cs, &circuit.zkey)
+                .with_context(|| format!("failed to load circuit {}", circuit.name))?;
+            let vk = ctx.verifying_key_in_hex();
+            circuits.insert(circuit.name.clone(), Circuit { ctx, vk });
+        }
+        Ok(circuits)
+    }
+
+    /// The number of permits for running proofs.
+    pub fn max_concurrent_proofs(&self) -> usize {
+        self.max_concurrent_proofs
+            .or_else(|| thread::available_parallelism().ok().map(usize::from))
+            .unwrap_or(1)
+    }
+}
@@ -0,0 +1,360 @@
+mod common;
+
+use anyhow::Context;
+use ark_bn254::{Bn254, Fq, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::Proof;
+use clap::Parser;
+use common::{Circuit, ServeArgs};
+use groth16::{
+    decode_public_input_array, do_prove, do_verify0, failure_cause, FailureCause, RapidSnarkProof,
+};
+use prost::bytes::Bytes;
+use prost::Message;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{transport::Server, Code, Request, Response, Status, Streaming};
+
+mod pb {
+    tonic::include_proto!("groth16.v1");
+}
+
+use pb::prover_server::{Prover, ProverServer};
+
+#[derive(Parser)]
+#[command(
+    name = "groth16-grpc",
+    version,
+    about = "Serve Groth16 (bn254) proving and verification over gRPC"
+)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:50051")]
+    listen: String,
+    #[command(flatten)]
+    serve: ServeArgs,
+}
+
+struct State {
+    circuits: HashMap<String, Circuit>,
+    permits: Arc<Semaphore>,
+}
+
+impl State {
+    fn circuit(&self, name: &str) -> Result<&Circuit, Failure> {
+        self.circuits.get(name).ok_or_else(|| Failure {
+            cause: pb::FailureCause::UnknownCircuit,
+            message: format!("unknown circuit {}", name),
+        })
+    }
+}
+
+/// An error on its way to the client, classified for the `ErrorDetail` status payload.
+struct Failure {
+    cause: pb::FailureCause,
+    message: String,
+}
+
+impl Failure {
+    fn invalid_input(message: impl ToString) -> Self {
+        Failure {
+            cause: pb::FailureCause::InvalidInput,
+            message: message.to_string(),
+        }
+    }
+
+    fn internal(message: impl ToString) -> Self {
+        Failure {
+            cause: pb::FailureCause::Internal,
+            message: message.to_string(),
+        }
+    }
+
+    fn detail(&self) -> pb::ErrorDetail {
+        pb::ErrorDetail {
+            cause: self.cause as i32,
+            message: self.message.clone(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for Failure {
+    fn from(err: anyhow::Error) -> Self {
+        let cause = match failure_cause(&err) {
+            FailureCause::InvalidArtifact => pb::FailureCause::InvalidArtifact,
+            FailureCause::InvalidInput => pb::FailureCause::InvalidInput,
+            FailureCause::InvalidVerifyingKey => pb::FailureCause::InvalidVerifyingKey,
+            FailureCause::WitnessGeneration => pb::FailureCause::WitnessGeneration,
+            FailureCause::UnsatisfiedConstraints => pb::FailureCause::UnsatisfiedConstraints,
+            FailureCause::Proving => pb::FailureCause::Proving,
+            FailureCause::UnknownCircuit => pb::FailureCause::UnknownCircuit,
+            FailureCause::UntrustedKey => pb::FailureCause::UntrustedKey,
+            FailureCause::Internal => pb::FailureCause::Internal,
+            FailureCause::Cancelled => pb::FailureCause::Cancelled,
+            FailureCause::TimedOut => pb::FailureCause::TimedOut,
+        };
+        Failure {
+            cause,
+            message: format!("{:#}", err),
+        }
+    }
+}
+
+impl From<Failure> for Status {
+    fn from(failure: Failure) -> Self {
+        let code = match failure.cause {
+            pb::FailureCause::UnknownCircuit => Code::NotFound,
+            pb::FailureCause::InvalidArtifact => Code::FailedPrecondition,
+            pb::FailureCause::UntrustedKey => Code::PermissionDenied,
+            pb::FailureCause::Cancelled => Code::Cancelled,
+            pb::FailureCause::TimedOut => Code::DeadlineExceeded,
+            pb::FailureCause::Proving
+            | pb::FailureCause::Internal
+            | pb::FailureCause::Unspecified => Code::Internal,
+            _ => Code::InvalidArgument,
+        };
+        let details = Bytes::from(failure.detail().encode_to_vec());
+        Status::with_details(code, failure.message, details)
+    }
+}
+
+#[derive(Clone)]
+struct Service {
+    state: Arc<State>,
+}
+
+impl Service {
+    /// Waits for one of the `--max-concurrent-proofs` slots.
+    async fn permit(&self) -> Result<OwnedSemaphorePermit, Failure> {
+        let permit = self
+            .state
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| anyhow::Error::from(e).context("prover is shutting down"))?;
+        Ok(permit)
+    }
+
+    /// Proves on a blocking thread, holding `permit` until the proof is done.
+    async fn prove_one(
+        &self,
+        permit: OwnedSemaphorePermit,
+        circuit: String,
+        input: String,
+    ) -> Result<pb::ProvingOutput, Failure> {
+        let state = self.state.clone();
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let circuit = state.circuit(&circuit)?;
+            let (pub_inputs, proof) = do_prove(&circuit.ctx, &input)?;
+            to_pb_output(pub_inputs, proof)
+        })
+        .await
+        .map_err(|e| anyhow::Error::from(e).context("proving task failed"))?
+    }
+}
+
+#[tonic::async_trait]
+impl Prover for Service {
+    async fn prove(
+        &self,
+        request: Request<pb::ProveRequest>,
+    ) -> Result<Response<pb::ProveResponse>, Status> {
+        let request = request.into_inner();
+        let permit = self.permit().await?;
+        let output = self
+            .prove_one(permit, request.circuit, request.input_json)
+            .await?;
+        Ok(Response::new(pb::ProveResponse {
+            id: request.id,
+            result: Some(pb::prove_response::Result::Output(output)),
+        }))
+    }
+
+    type ProveBatchStream = ReceiverStream<Result<pb::ProveResponse, Status>>;
+
+    async fn prove_batch(
+        &self,
+        request: Request<Streaming<pb::ProveRequest>>,
+    ) -> Result<Response<Self::ProveBatchStream>, Status> {
+        let mut requests = request.into_inner();
+        let (tx, rx) = mpsc::channel(16);
+        let service = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match requests.message().await {
+                    Ok(Some(request)) => {
+                        // The stream is only read on once a proof may start, so that a client cannot queue up
+                        // proofs beyond the limit.
+                        let permit = service.permit().await;
+                        let service = service.clone();
+                        let tx = tx.clone();
+                        tokio::spawn(async move {
+                            let output = match permit {
+                                Ok(permit) => {
+                                    service
+                                        .prove_one(permit, request.circuit, request.input_json)
+                                        .await
+                                }
+                                Err(failure) => Err(failure),
+                            };
+                            let result = match output {
+                                Ok(output) => pb::prove_response::Result::Output(output),
+                                Err(failure) => pb::prove_response::Result::Error(failure.detail()),
+                            };
+                            let response = pb::ProveResponse {
+                                id: request.id,
+                                result: Some(result),
+                            };
+                            let _ = tx.send(Ok(response)).await;
+                        });
+                    }
+                    Ok(None) => break,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn verify(
+        &self,
+        request: Request<pb::VerifyRequest>,
+    ) -> Result<Response<pb::VerifyResponse>, Status> {
+        let request = request.into_inner();
+        let vk = match request.key {
+            Some(pb::verify_request::Key::Circuit(name)) => self.state.circuit(&name)?.vk.clone(),
+            Some(pb::verify_request::Key::VerifyingKey(vk)) => vk,
+            None => {
+                return Err(Failure::invalid_input("circuit or verifying_key is required").into())
+            }
+        };
+        let output = request
+            .output
+            .ok_or_else(|| Failure::invalid_input("output is required"))?;
+        let valid = tokio::task::spawn_blocking(move || verify_pb_output(&vk, output))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))??;
+        Ok(Response::new(pb::VerifyResponse { valid }))
+    }
+
+    async fn get_verifying_key(
+        &self,
+        request: Request<pb::GetVerifyingKeyRequest>,
+    ) -> Result<Response<pb::GetVerifyingKeyResponse>, Status> {
+        let circuit = self.state.circuit(&request.get_ref().circuit)?;
+        Ok(Response::new(pb::GetVerifyingKeyResponse {
+            verifying_key: circuit.vk.clone(),
+        }))
+    }
+
+    async fn list_circuits(
+        &self,
+        _request: Request<pb::ListCircuitsRequest>,
+    ) -> Result<Response<pb::ListCircuitsResponse>, Status> {
+        let mut circuits: Vec<_> = self
+            .state
+            .circuits
+            .iter()
+            .map(|(name, circuit)| pb::CircuitInfo {
+                name: name.clone(),
+                verifying_key: circuit.vk.clone(),
+            })
+            .collect();
+        circuits.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Response::new(pb::ListCircuitsResponse { circuits }))
+    }
+}
+
+/// Builds the message from the proof itself, rather than from its JSON, so that no field can silently come out empty.
+fn to_pb_output(public_inputs: Vec<Fr>, proof: Proof<Bn254>) -> Result<pb::ProvingOutput, Failure> {
+    let proof = RapidSnarkProof::from(proof);
+    let pi_b = proof
+        .pi_b
+        .iter()
+        .map(|row| match &row[..] {
+            [c0, c1] => Ok(pb::Fq2 {
+                c0: decimal(c0),
+                c1: decimal(c1),
+            }),
+            _ => Err(Failure::internal(format!(
+                "expected 2 coordinates in pi_b, got {}",
+                row.len()
+            ))),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(pb::ProvingOutput {
+        public_inputs: public_inputs.iter().map(decimal).collect(),
+        proof: Some(pb::Proof {
+            pi_a: proof.pi_a.iter().map(decimal).collect(),
+            pi_b,
+            pi_c: proof.pi_c.iter().map(decimal).collect(),
+            protocol: proof.protocol,
+        }),
+    })
+}
+
+fn decimal<F: PrimeField>(value: &F) -> String {
+    value.into_bigint().to_string()
+}
+
+fn verify_pb_output(vk: &str, output: pb::ProvingOutput) -> Result<bool, Failure> {
+    let parse = |values: &[String]| -> Result<Vec<Fq>, Failure> {
+        values
+            .iter()
+            .map(|v| {
+                v.parse::<Fq>()
+                    .map_err(|_| Failure::invalid_input(format!("invalid field element {}", v)))
+            })
+            .collect()
+    };
+    let proof = output
+        .proof
+        .ok_or_else(|| Failure::invalid_input("proof is required"))?;
+    let proof = RapidSnarkProof {
+        pi_a: parse(&proof.pi_a)?,
+        pi_b: proof
+            .pi_b
+            .iter()
+            .map(|fq2| parse(&[fq2.c0.clone(), fq2.c1.clone()]))
+            .collect::<Result<_, _>>()?,
+        pi_c: parse(&proof.pi_c)?,
+        protocol: proof.protocol,
+    };
+    let vk = hex::decode(vk)
+        .context("failed to decode VerifyingKey")
+        .map_err(Failure::from)?;
+    let inputs = decode_public_input_array(output.public_inputs)?;
+    Ok(do_verify0(vk, proof.try_into()?, inputs)?)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let circuits = args.serve.load_circuits()?;
+    let max_concurrent_proofs = args.serve.max_concurrent_proofs();
+    let service = Service {
+        state: Arc::new(State {
+            circuits,
+            permits: Arc::new(Semaphore::new(max_concurrent_proofs)),
+        }),
+    };
+
+    let addr = args
+        .listen
+        .parse()
+        .with_context(|| format!("invalid listen address {}", args.listen))?;
+    eprintln!("listening on {}", args.listen);
+    Server::builder()
+        .add_service(ProverServer::new(service))
+        .serve(addr)
+        .await
+        .context("server failed")
+}
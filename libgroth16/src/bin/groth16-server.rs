@@ -0,0 +1,195 @@
+mod common;
+
+use anyhow::Context;
+use clap::Parser;
+use common::{Circuit, ServeArgs};
+use groth16::{do_prove, do_verify, failure_cause, serialize, FailureCause};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Circuit inputs and proving outputs are a few KB; anything much larger is rejected unread.
+const MAX_BODY_LEN: usize = 4 << 20;
+
+#[derive(Parser)]
+#[command(
+    name = "groth16-server",
+    version,
+    about = "Serve Groth16 (bn254) proving and verification over HTTP"
+)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: String,
+    #[command(flatten)]
+    serve: ServeArgs,
+    /// Number of threads accepting requests
+    #[arg(long, default_value_t = 8)]
+    workers: usize,
+}
+
+struct State {
+    circuits: HashMap<String, Circuit>,
+    running_proofs: AtomicUsize,
+    max_concurrent_proofs: usize,
+}
+
+/// Slot for one running proof, released on drop.
+struct ProofPermit<'a>(&'a AtomicUsize);
+
+impl Drop for ProofPermit<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl State {
+    fn try_acquire(&self) -> Option<ProofPermit<'_>> {
+        self.running_proofs
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+                (running < self.max_concurrent_proofs).then_some(running + 1)
+            })
+            .ok()
+            .map(|_| ProofPermit(&self.running_proofs))
+    }
+
+    fn circuit(&self, query: &HashMap<&str, &str>) -> Result<(&str, &Circuit), HttpError> {
+        match query.get("circuit") {
+            Some(name) => self
+                .circuits
+                .get_key_value(*name)
+                .map(|(name, circuit)| (name.as_str(), circuit))
+                .ok_or_else(|| HttpError(404, format!("unknown circuit {}", name))),
+            None if self.circuits.len() == 1 => {
+                let (name, circuit) = self.circuits.iter().next().unwrap();
+                Ok((name.as_str(), circuit))
+            }
+            None => Err(HttpError(
+                400,
+                "circuit query parameter is required".to_string(),
+            )),
+        }
+    }
+}
+
+struct HttpError(u16, String);
+
+impl From<anyhow::Error> for HttpError {
+    fn from(err: anyhow::Error) -> Self {
+        let status = match failure_cause(&err) {
+            FailureCause::UnknownCircuit => 404,
+            FailureCause::UntrustedKey => 403,
+            FailureCause::Cancelled | FailureCause::TimedOut => 503,
+            FailureCause::InvalidArtifact | FailureCause::Proving | FailureCause::Internal => 500,
+            FailureCause::InvalidInput
+            | FailureCause::InvalidVerifyingKey
+            | FailureCause::WitnessGeneration
+            | FailureCause::UnsatisfiedConstraints => 400,
+        };
+        HttpError(status, format!("{:#}", err))
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let circuits = args.serve.load_circuits()?;
+    let max_concurrent_proofs = args.serve.max_concurrent_proofs();
+    let state = Arc::new(State {
+        circuits,
+        running_proofs: AtomicUsize::new(0),
+        max_concurrent_proofs,
+    });
+
+    let server = Arc::new(
+        Server::http(&args.listen)
+            .map_err(|e| anyhow::anyhow!(e))
+            .with_context(|| format!("failed to listen on {}", args.listen))?,
+    );
+    eprintln!("listening on {}", args.listen);
+
+    let workers: Vec<_> = (0..args.workers.max(1))
+        .map(|_| {
+            let server = server.clone();
+            let state = state.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(&state, request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+fn handle(state: &State, mut request: Request) {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let query: HashMap<&str, &str> = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect();
+
+    let result = match (request.method(), path) {
+        (Method::Post, "/prove") => {
+            read_body(&mut request).and_then(|body| prove(state, &query, &body))
+        }
+        (Method::Post, "/verify") => {
+            read_body(&mut request).and_then(|body| verify(state, &query, &body))
+        }
+        (Method::Get, "/vk") => state
+            .circuit(&query)
+            .map(|(name, circuit)| json!({ "circuit": name, "verifying_key": circuit.vk })),
+        _ => Err(HttpError(404, format!("no route for {}", path))),
+    };
+
+    let (status, body) = match result {
+        Ok(body) => (200, body),
+        Err(HttpError(status, message)) => (status, json!({ "error": message })),
+    };
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header);
+    let _ = request.respond(response);
+}
+
+fn read_body(request: &mut Request) -> Result<String, HttpError> {
+    let too_large = || HttpError(413, format!("body exceeds {} bytes", MAX_BODY_LEN));
+    if matches!(request.body_length(), Some(len) if len > MAX_BODY_LEN) {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_LEN as u64 + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| HttpError(400, format!("failed to read body: {}", e)))?;
+    if body.len() > MAX_BODY_LEN {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+fn prove(state: &State, query: &HashMap<&str, &str>, body: &str) -> Result<Value, HttpError> {
+    let (_, circuit) = state.circuit(query)?;
+    let _permit = state
+        .try_acquire()
+        .ok_or_else(|| HttpError(503, "too many proofs in progress".to_string()))?;
+    let (pub_inputs, proof) = do_prove(&circuit.ctx, body)?;
+    let output = serialize(pub_inputs, proof)?;
+    Ok(serde_json::from_str(&output).map_err(anyhow::Error::from)?)
+}
+
+fn verify(state: &State, query: &HashMap<&str, &str>, body: &str) -> Result<Value, HttpError> {
+    let (_, circuit) = state.circuit(query)?;
+    let valid = do_verify(&circuit.vk, body)?;
+    Ok(json!({ "valid": valid }))
+}
@@ -0,0 +1,235 @@
+use anyhow::Context;
+use ark_bn254::Bn254;
+use ark_circom::read_zkey;
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use clap::{Parser, Subcommand, ValueEnum};
+use groth16::{
+    do_prove, do_verify, inspect_circuit, load_context, read_input_schema, serialize,
+    verify_guardian_proof, RapidSnarkProof,
+};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::Read;
+use std::process::ExitCode;
+
+/// Exit code for a proof that was checked and found invalid.
+const EXIT_INVALID: u8 = 1;
+/// Exit code for any failure to run the command (bad arguments, unreadable files, ...).
+const EXIT_ERROR: u8 = 2;
+
+#[derive(Parser)]
+#[command(
+    name = "groth16",
+    version,
+    about = "Groth16 (bn254) proving and verification tool"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a proof and print the proving output
+    Prove {
+        #[arg(long)]
+        wasm: String,
+        #[arg(long)]
+        r1cs: String,
+        #[arg(long)]
+        zkey: String,
+        /// Circuit input JSON file, `-` for stdin
+        #[arg(long)]
+        input: String,
+    },
+    /// Verify a proving output against a verifying key
+    Verify {
+        /// Verifying key file (hex, or the JSON printed by `export-vk`), `-` for stdin
+        #[arg(long)]
+        vk: String,
+        /// Proving output JSON file, `-` for stdin
+        #[arg(long)]
+        proof: String,
+        /// JWKS file of trusted issuer keys, to also check a guardianhash proof's pubkey
+        #[arg(long)]
+        jwks: Option<String>,
+    },
+    /// Print the verifying key of a zkey in compressed hex
+    ExportVk {
+        #[arg(long)]
+        zkey: String,
+    },
+    /// Print circuit statistics read from the r1cs header
+    Inspect {
+        #[arg(long)]
+        r1cs: String,
+        /// Symbol file, to also print the names of the public signals
+        #[arg(long)]
+        sym: Option<String>,
+    },
+    /// Print the input signals of a circuit and their dimensions
+    Schema {
+        #[arg(long)]
+        r1cs: String,
+        #[arg(long)]
+        sym: String,
+    },
+    /// Convert a proof or a verifying key between encodings
+    Convert {
+        /// Proof file: rapidsnark JSON, proving output JSON or compressed hex
+        #[arg(long, conflicts_with = "vk", required_unless_present = "vk")]
+        proof: Option<String>,
+        /// Verifying key file: compressed or uncompressed hex
+        #[arg(long)]
+        vk: Option<String>,
+        #[arg(long, value_enum)]
+        to: Format,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// rapidsnark / snarkjs JSON (proofs only)
+    Json,
+    /// arkworks compressed serialization, hex encoded
+    Compressed,
+    /// arkworks uncompressed serialization, hex encoded
+    Uncompressed,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        Ok((output, code)) => {
+            println!("{}", output);
+            ExitCode::from(code)
+        }
+        Err(err) => {
+            eprintln!("{}", json!({ "error": format!("{:#}", err) }));
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+fn run(command: Command) -> anyhow::Result<(Value, u8)> {
+    match command {
+        Command::Prove {
+            wasm,
+            r1cs,
+            zkey,
+            input,
+        } => {
+            let ctx = load_context(&wasm, &r1cs, &zkey)?;
+            let input = read_source(&input)?;
+            let (pub_inputs, proof) = do_prove(&ctx, &input)?;
+            let output = serialize(pub_inputs, proof)?;
+            Ok((serde_json::from_str(&output)?, 0))
+        }
+        Command::Verify { vk, proof, jwks } => {
+            let vk = read_verifying_key(&vk)?;
+            let proving_output = read_source(&proof)?;
+            if let Some(jwks) = jwks {
+                let verification =
+                    verify_guardian_proof(&vk, &proving_output, &read_source(&jwks)?)?;
+                let code = if verification.valid { 0 } else { EXIT_INVALID };
+                return Ok((serde_json::to_value(verification)?, code));
+            }
+            let valid = do_verify(&vk, &proving_output)?;
+            let code = if valid { 0 } else { EXIT_INVALID };
+            Ok((json!({ "valid": valid }), code))
+        }
+        Command::ExportVk { zkey } => {
+            let mut file = File::open(&zkey).with_context(|| format!("failed to read {}", zkey))?;
+            let (pk, _) = read_zkey(&mut file).context("failed to load zkey")?;
+            let mut vk = Vec::new();
+            pk.vk.serialize_compressed(&mut vk)?;
+            Ok((json!({ "verifying_key": hex::encode(vk) }), 0))
+        }
+        Command::Inspect { r1cs, sym } => {
+            let info = inspect_circuit(&r1cs, sym.as_deref())?;
+            Ok((serde_json::to_value(info)?, 0))
+        }
+        Command::Schema { r1cs, sym } => {
+            let schema = read_input_schema(&r1cs, &sym)?;
+            Ok((json!({ "inputs": schema }), 0))
+        }
+        Command::Convert { proof, vk, to } => match (proof, vk) {
+            (Some(proof), _) => Ok((json!({ "proof": convert_proof(&proof, to)? }), 0)),
+            (_, Some(vk)) => Ok((json!({ "verifying_key": convert_vk(&vk, to)? }), 0)),
+            _ => anyhow::bail!("either --proof or --vk is required"),
+        },
+    }
+}
+
+fn read_source(path: &str) -> anyhow::Result<String> {
+    let mut content = String::new();
+    if path == "-" {
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .context("failed to read stdin")?;
+    } else {
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut content))
+            .with_context(|| format!("failed to read {}", path))?;
+    }
+    Ok(content.trim().to_string())
+}
+
+fn read_verifying_key(path: &str) -> anyhow::Result<String> {
+    let content = read_source(path)?;
+    if !content.starts_with('{') {
+        return Ok(content);
+    }
+    let value: Value = serde_json::from_str(&content).context("failed to parse JSON")?;
+    value["verifying_key"]
+        .as_str()
+        .map(str::to_owned)
+        .context("expected a verifying_key field")
+}
+
+fn convert_proof(path: &str, to: Format) -> anyhow::Result<Value> {
+    let content = read_source(path)?;
+    let proof: Proof<Bn254> = if content.starts_with('{') {
+        let mut value: Value = serde_json::from_str(&content).context("failed to parse JSON")?;
+        // Accept a full proving output as well as a bare proof object.
+        if let Some(inner) = value.get_mut("proof") {
+            value = inner.take();
+        }
+        serde_json::from_value::<RapidSnarkProof>(value)
+            .context("failed to decode proof")?
+            .try_into()?
+    } else {
+        let bytes = hex::decode(&content).context("failed to decode hex")?;
+        Proof::<Bn254>::deserialize_compressed(&*bytes)
+            .or_else(|_| Proof::<Bn254>::deserialize_uncompressed(&*bytes))
+            .context("failed to decode proof")?
+    };
+    match to {
+        Format::Json => Ok(serde_json::to_value(RapidSnarkProof::from(proof))?),
+        Format::Compressed => {
+            let mut bytes = Vec::new();
+            proof.serialize_compressed(&mut bytes)?;
+            Ok(Value::String(hex::encode(bytes)))
+        }
+        Format::Uncompressed => {
+            let mut bytes = Vec::new();
+            proof.serialize_uncompressed(&mut bytes)?;
+            Ok(Value::String(hex::encode(bytes)))
+        }
+    }
+}
+
+fn convert_vk(path: &str, to: Format) -> anyhow::Result<Value> {
+    let bytes = hex::decode(read_verifying_key(path)?).context("failed to decode hex")?;
+    let vk = VerifyingKey::<Bn254>::deserialize_compressed(&*bytes)
+        .or_else(|_| VerifyingKey::<Bn254>::deserialize_uncompressed(&*bytes))
+        .context("failed to decode verifying key")?;
+    let mut bytes = Vec::new();
+    match to {
+        Format::Json => anyhow::bail!("verifying keys have no JSON encoding"),
+        Format::Compressed => vk.serialize_compressed(&mut bytes)?,
+        Format::Uncompressed => vk.serialize_uncompressed(&mut bytes)?,
+    }
+    Ok(Value::String(hex::encode(bytes)))
+}
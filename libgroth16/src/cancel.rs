@@ -0,0 +1,147 @@
+use ark_ff::Field;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Stops a proof in flight, on request or at a deadline. Proving checks the token between its phases: before and after
+/// witness calculation, before `Groth16::prove`, and inside it once the constraints are synthesized, before the
+/// witness map FFT and the MSMs. Neither those nor the wasm witness calculator can be interrupted, so a proof may run
+/// on for the length of one of them after the token fired.
+#[derive(Debug, Default)]
+pub struct CancelToken {
+    cancelled: AtomicBool,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that also fires once `timeout` has passed.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        CancelToken {
+            cancelled: AtomicBool::new(false),
+            deadline: Instant::now().checked_add(timeout),
+        }
+    }
+
+    /// The C API's form of a timeout, where 0 means none.
+    pub(crate) fn with_timeout_ms(timeout_ms: u64) -> Self {
+        match timeout_ms {
+            0 => CancelToken::new(),
+            _ => CancelToken::with_timeout(Duration::from_millis(timeout_ms)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fails with a [`CancelledError`] or [`TimedOutError`] once the token fired.
+    pub(crate) fn check(&self) -> anyhow::Result<()> {
+        if self.is_cancelled() {
+            return Err(CancelledError.into());
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(TimedOutError.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A circuit that checks the token after synthesizing its constraints, which is the last point `Groth16::prove` calls
+/// back into before the FFT and the MSMs. A fired token aborts synthesis with a placeholder error; callers check the
+/// token again to report the actual cause.
+pub(crate) struct CheckedCircuit<'a, C> {
+    pub(crate) circuit: C,
+    pub(crate) token: Option<&'a CancelToken>,
+}
+
+impl<F: Field, C: ConstraintSynthesizer<F>> ConstraintSynthesizer<F> for CheckedCircuit<'_, C> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        self.circuit.generate_constraints(cs)?;
+        match self.token.map(CancelToken::check) {
+            Some(Err(_)) => Err(SynthesisError::AssignmentMissing),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct CancelledError;
+
+impl Display for CancelledError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "proving was cancelled")
+    }
+}
+
+impl std::error::Error for CancelledError {}
+
+#[derive(Debug)]
+pub(crate) struct TimedOutError;
+
+impl Display for TimedOutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "proving deadline exceeded")
+    }
+}
+
+impl std::error::Error for TimedOutError {}
+
+#[cfg(test)]
+mod cancel_test {
+    use crate::cancel::{CancelToken, CheckedCircuit};
+    use crate::utils::{failure_cause, FailureCause};
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::{
+        ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError,
+    };
+    use std::time::Duration;
+
+    struct EmptyCircuit;
+
+    impl ConstraintSynthesizer<Fr> for EmptyCircuit {
+        fn generate_constraints(self, _: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let token = CancelToken::new();
+        assert!(token.check().is_ok());
+        token.cancel();
+        let err = token.check().unwrap_err();
+        assert_eq!(FailureCause::Cancelled, failure_cause(&err));
+
+        let token = CancelToken::with_timeout(Duration::ZERO);
+        let err = token.check().unwrap_err();
+        assert_eq!(FailureCause::TimedOut, failure_cause(&err));
+        assert!(CancelToken::with_timeout(Duration::from_secs(60))
+            .check()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_checked_circuit() {
+        let synthesize = |token: Option<&CancelToken>| {
+            let circuit = CheckedCircuit {
+                circuit: EmptyCircuit,
+                token,
+            };
+            circuit.generate_constraints(ConstraintSystem::<Fr>::new_ref())
+        };
+        let token = CancelToken::new();
+        assert!(synthesize(None).is_ok());
+        assert!(synthesize(Some(&token)).is_ok());
+        token.cancel();
+        assert!(synthesize(Some(&token)).is_err());
+    }
+}
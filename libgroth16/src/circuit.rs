@@ -0,0 +1,414 @@
+use anyhow::Context;
+use num_bigint::BigUint;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+const BN254_PRIME: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+/// Counts from the header section of an r1cs file.
+/// Spec: <https://github.com/iden3/r1csfile/blob/master/doc/r1cs_bin_format.md>
+#[derive(Clone, Debug)]
+pub(crate) struct R1csHeader {
+    pub(crate) prime: BigUint,
+    pub(crate) n_wires: u32,
+    pub(crate) n_pub_out: u32,
+    pub(crate) n_pub_in: u32,
+    pub(crate) n_prv_in: u32,
+    pub(crate) n_labels: u64,
+    pub(crate) n_constraints: u32,
+}
+
+impl R1csHeader {
+    pub(crate) fn read<R: Read + Seek>(mut reader: R) -> anyhow::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        anyhow::ensure!(&magic == b"r1cs", "not an r1cs file");
+        let _version = read_u32(&mut reader)?;
+        let n_sections = read_u32(&mut reader)?;
+        for _ in 0..n_sections {
+            let section_type = read_u32(&mut reader)?;
+            let section_size = read_u64(&mut reader)?;
+            if section_type != 1 {
+                reader.seek(SeekFrom::Current(section_size as i64))?;
+                continue;
+            }
+            let field_size = read_u32(&mut reader)?;
+            let mut prime = vec![0u8; field_size as usize];
+            reader.read_exact(&mut prime)?;
+            return Ok(R1csHeader {
+                prime: BigUint::from_bytes_le(&prime),
+                n_wires: read_u32(&mut reader)?,
+                n_pub_out: read_u32(&mut reader)?,
+                n_pub_in: read_u32(&mut reader)?,
+                n_prv_in: read_u32(&mut reader)?,
+                n_labels: read_u64(&mut reader)?,
+                n_constraints: read_u32(&mut reader)?,
+            });
+        }
+        anyhow::bail!("r1cs file has no header section")
+    }
+
+    pub(crate) fn from_file(path: &str) -> anyhow::Result<Self> {
+        let file = File::open(path).context("invalid r1cs file")?;
+        R1csHeader::read(BufReader::new(file)).context("failed to read r1cs header")
+    }
+
+    /// Number of public signals: outputs come first, then public inputs.
+    pub(crate) fn n_public(&self) -> usize {
+        (self.n_pub_out + self.n_pub_in) as usize
+    }
+
+    /// Number of input values: public inputs, then private inputs.
+    pub(crate) fn n_inputs(&self) -> usize {
+        (self.n_pub_in + self.n_prv_in) as usize
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> std::io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Signal names from a circom `.sym` file, keyed by witness index.
+pub(crate) struct SymbolTable {
+    names: HashMap<usize, String>,
+}
+
+impl SymbolTable {
+    /// Each line is `label_index,witness_index,component_index,name`; signals removed by the optimizer have a witness
+    /// index of -1 and are skipped.
+    pub(crate) fn parse(content: &str) -> anyhow::Result<Self> {
+        let mut names = HashMap::new();
+        for (line_no, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<_> = line.splitn(4, ',').collect();
+            let (witness, name) = match fields[..] {
+                [_, witness, _, name] => (witness, name),
+                _ => anyhow::bail!("invalid symbol on line {}", line_no + 1),
+            };
+            let witness: i64 = witness
+                .parse()
+                .with_context(|| format!("invalid witness index on line {}", line_no + 1))?;
+            if witness >= 0 {
+                names.insert(witness as usize, name.trim().to_string());
+            }
+        }
+        Ok(SymbolTable { names })
+    }
+
+    pub(crate) fn from_file(path: &str) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path).context("invalid sym file")?;
+        SymbolTable::parse(&content).context("failed to parse sym file")
+    }
+
+    pub(crate) fn name(&self, witness: usize) -> Option<&str> {
+        self.names.get(&witness).map(String::as_str)
+    }
+}
+
+#[derive(Serialize)]
+pub struct CircuitInfo {
+    /// `bn254` when the circuit uses the scalar field of bn254, otherwise `unknown`.
+    pub curve: String,
+    /// The field modulus in decimal.
+    pub prime: String,
+    pub constraints: u32,
+    pub wires: u32,
+    pub labels: u64,
+    pub outputs: u32,
+    pub public_inputs: u32,
+    pub private_inputs: u32,
+    /// Names of the public signals, in the order of `ProvingOutput.public_inputs`. Only known with a `.sym` file.
+    pub public_signals: Option<Vec<Option<String>>>,
+}
+
+pub(crate) fn circuit_info(header: &R1csHeader, symbols: Option<&SymbolTable>) -> CircuitInfo {
+    let prime = header.prime.to_string();
+    CircuitInfo {
+        curve: if prime == BN254_PRIME {
+            "bn254"
+        } else {
+            "unknown"
+        }
+        .to_string(),
+        prime,
+        constraints: header.n_constraints,
+        wires: header.n_wires,
+        labels: header.n_labels,
+        outputs: header.n_pub_out,
+        public_inputs: header.n_pub_in,
+        private_inputs: header.n_prv_in,
+        public_signals: symbols.map(|symbols| {
+            (1..=header.n_public())
+                .map(|witness| symbols.name(witness).map(str::to_owned))
+                .collect()
+        }),
+    }
+}
+
+/// Describes a circuit from its r1cs file and, optionally, its `.sym` file, without loading the wasm or zkey.
+pub fn inspect_circuit(r1cs_path: &str, sym_path: Option<&str>) -> anyhow::Result<CircuitInfo> {
+    let header = R1csHeader::from_file(r1cs_path)?;
+    let symbols = sym_path.map(SymbolTable::from_file).transpose()?;
+    Ok(circuit_info(&header, symbols.as_ref()))
+}
+
+/// An input signal of the main component. `dimensions` is empty for a scalar signal.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct InputSignal {
+    pub name: String,
+    pub dimensions: Vec<usize>,
+    pub public: bool,
+}
+
+impl InputSignal {
+    /// Number of values the signal takes in the input JSON, where arrays are flattened.
+    pub fn size(&self) -> usize {
+        self.dimensions.iter().product()
+    }
+}
+
+/// Derives the inputs of the main component from the symbol table. The wasm only knows the inputs by a hash of their
+/// name, so the names come from the `.sym` file; the inputs occupy the witness right after the outputs.
+pub(crate) fn input_schema(
+    header: &R1csHeader,
+    symbols: &SymbolTable,
+) -> anyhow::Result<Vec<InputSignal>> {
+    let first = header.n_pub_out as usize + 1;
+    let mut signals: Vec<InputSignal> = Vec::new();
+    for witness in first..first + header.n_inputs() {
+        let symbol = symbols
+            .name(witness)
+            .with_context(|| format!("input signal {} has no name", witness))?;
+        let (name, indices) =
+            parse_signal_name(symbol).with_context(|| format!("invalid signal name {}", symbol))?;
+        let public = witness < first + header.n_pub_in as usize;
+        match signals.last_mut() {
+            Some(last) if last.name == name && last.dimensions.len() == indices.len() => {
+                for (dimension, index) in last.dimensions.iter_mut().zip(indices) {
+                    *dimension = (*dimension).max(index + 1);
+                }
+            }
+            _ => signals.push(InputSignal {
+                name: name.to_string(),
+                dimensions: indices.into_iter().map(|index| index + 1).collect(),
+                public,
+            }),
+        }
+    }
+    Ok(signals)
+}
+
+/// Splits `main.jwt[3]` into `jwt` and `[3]`.
+fn parse_signal_name(symbol: &str) -> anyhow::Result<(&str, Vec<usize>)> {
+    let symbol = symbol.strip_prefix("main.").unwrap_or(symbol);
+    let (name, mut rest) = symbol.split_at(symbol.find('[').unwrap_or(symbol.len()));
+    let mut indices = Vec::new();
+    while !rest.is_empty() {
+        let end = rest.find(']').context("unclosed index")?;
+        anyhow::ensure!(rest.starts_with('['), "unexpected {}", rest);
+        indices.push(rest[1..end].parse()?);
+        rest = &rest[end + 1..];
+    }
+    Ok((name, indices))
+}
+
+/// Input that does not match the circuit's inputs, listing every mismatch.
+#[derive(Debug)]
+pub(crate) struct InputMismatchError {
+    mismatches: Vec<String>,
+}
+
+impl Display for InputMismatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.mismatches.join("; "))
+    }
+}
+
+impl std::error::Error for InputMismatchError {}
+
+/// The input schema is needed, but the `.sym` file that names the inputs is not loaded.
+#[derive(Debug)]
+pub(crate) struct SchemaUnavailableError;
+
+impl Display for SchemaUnavailableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "input schema unavailable, the circuit's .sym file is not loaded"
+        )
+    }
+}
+
+impl std::error::Error for SchemaUnavailableError {}
+
+/// Checks the input sizes against the schema, taking `sizes` as signal name and number of values.
+pub(crate) fn check_inputs<'a>(
+    schema: &[InputSignal],
+    sizes: impl IntoIterator<Item = (&'a str, usize)>,
+) -> Result<(), InputMismatchError> {
+    let sizes: HashMap<&str, usize> = sizes.into_iter().collect();
+    let mut mismatches = Vec::new();
+    for signal in schema {
+        match sizes.get(signal.name.as_str()) {
+            None => mismatches.push(format!("missing signal {}", signal.name)),
+            Some(&size) if size != signal.size() => mismatches.push(format!(
+                "signal {} expects {} values, got {}",
+                signal.name,
+                signal.size(),
+                size
+            )),
+            _ => {}
+        }
+    }
+    let mut unknown: Vec<_> = sizes
+        .keys()
+        .filter(|name| !schema.iter().any(|signal| signal.name == **name))
+        .collect();
+    unknown.sort();
+    for name in unknown {
+        mismatches.push(format!("unknown signal {}", name));
+    }
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(InputMismatchError { mismatches })
+    }
+}
+
+/// Lists the inputs of a circuit from its r1cs and `.sym` files.
+pub fn read_input_schema(r1cs_path: &str, sym_path: &str) -> anyhow::Result<Vec<InputSignal>> {
+    let header = R1csHeader::from_file(r1cs_path)?;
+    let symbols = SymbolTable::from_file(sym_path)?;
+    input_schema(&header, &symbols)
+}
+
+#[cfg(test)]
+mod circuit_test {
+    use crate::circuit::{
+        check_inputs, circuit_info, input_schema, InputSignal, R1csHeader, SchemaUnavailableError,
+        SymbolTable, BN254_PRIME,
+    };
+    use crate::utils::{failure_cause, FailureCause};
+    use num_bigint::BigUint;
+    use std::io::Cursor;
+
+    fn r1cs_bytes() -> Vec<u8> {
+        let prime: BigUint = BN254_PRIME.parse().unwrap();
+        let mut prime = prime.to_bytes_le();
+        prime.resize(32, 0);
+
+        let mut header = Vec::new();
+        header.extend(32u32.to_le_bytes());
+        header.extend(prime);
+        for count in [10u32, 1, 2, 3] {
+            header.extend(count.to_le_bytes());
+        }
+        header.extend(12u64.to_le_bytes());
+        header.extend(5u32.to_le_bytes());
+
+        let mut bytes = b"r1cs".to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+        // A constraints section before the header, which the reader has to skip.
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(3u64.to_le_bytes());
+        bytes.extend([0u8; 3]);
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend((header.len() as u64).to_le_bytes());
+        bytes.extend(header);
+        bytes
+    }
+
+    #[test]
+    fn test_read_header() {
+        let header = R1csHeader::read(Cursor::new(r1cs_bytes())).unwrap();
+        assert_eq!(10, header.n_wires);
+        assert_eq!(1, header.n_pub_out);
+        assert_eq!(2, header.n_pub_in);
+        assert_eq!(3, header.n_prv_in);
+        assert_eq!(12, header.n_labels);
+        assert_eq!(5, header.n_constraints);
+        assert!(R1csHeader::read(Cursor::new(b"wasm".to_vec())).is_err());
+    }
+
+    #[test]
+    fn test_public_signal_names() {
+        let header = R1csHeader::read(Cursor::new(r1cs_bytes())).unwrap();
+        let symbols = SymbolTable::parse(
+            "1,1,0,main.out\n2,2,0,main.pubkey[0]\n3,3,0,main.pubkey[1]\n4,-1,0,main.tmp\n5,4,0,main.salt[0]\n",
+        )
+        .unwrap();
+        let info = circuit_info(&header, Some(&symbols));
+        assert_eq!("bn254", info.curve);
+        assert_eq!(
+            Some(vec![
+                Some("main.out".to_string()),
+                Some("main.pubkey[0]".to_string()),
+                Some("main.pubkey[1]".to_string()),
+            ]),
+            info.public_signals
+        );
+        assert!(circuit_info(&header, None).public_signals.is_none());
+        assert!(SymbolTable::parse("1,x,0,main.out").is_err());
+    }
+
+    #[test]
+    fn test_input_schema() {
+        let header = R1csHeader::read(Cursor::new(r1cs_bytes())).unwrap();
+        let symbols = SymbolTable::parse(
+            "1,1,0,main.out\n2,2,0,main.pubkey[0]\n3,3,0,main.pubkey[1]\n4,4,0,main.salt[0][0]\n5,5,0,main.salt[0][1]\n6,6,0,main.nonce\n7,7,1,main.hasher.out\n",
+        )
+        .unwrap();
+        let schema = input_schema(&header, &symbols).unwrap();
+        assert_eq!(
+            vec![
+                InputSignal {
+                    name: "pubkey".to_string(),
+                    dimensions: vec![2],
+                    public: true,
+                },
+                InputSignal {
+                    name: "salt".to_string(),
+                    dimensions: vec![1, 2],
+                    public: false,
+                },
+                InputSignal {
+                    name: "nonce".to_string(),
+                    dimensions: vec![],
+                    public: false,
+                },
+            ],
+            schema
+        );
+
+        assert!(check_inputs(&schema, [("pubkey", 2), ("salt", 2), ("nonce", 1)]).is_ok());
+        let err = check_inputs(&schema, [("pubkey", 3), ("nonce", 1), ("jwt", 1)]).unwrap_err();
+        assert_eq!(
+            "signal pubkey expects 2 values, got 3; missing signal salt; unknown signal jwt",
+            err.to_string()
+        );
+        // The same number of values in total, which a count check alone would let through.
+        let err = check_inputs(&schema, [("pubkey", 4), ("nonce", 1)]).unwrap_err();
+        assert_eq!(
+            "signal pubkey expects 2 values, got 4; missing signal salt",
+            err.to_string()
+        );
+
+        let err = anyhow::Error::from(SchemaUnavailableError);
+        assert_eq!(FailureCause::InvalidArtifact, failure_cause(&err));
+    }
+}
@@ -1,8 +1,13 @@
-use serde::{Deserialize, Serialize};
 use crate::proof::RapidSnarkProof;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize)]
-pub(crate) struct ProvingOutput {
-    pub(crate) public_inputs: Vec<String>,
-    pub(crate) proof: RapidSnarkProof,
+pub struct ProvingOutput {
+    #[serde(default)]
+    pub public_inputs: Vec<String>,
+    /// Public signal values keyed by signal name, e.g. `main.pubkey[0]`. Only emitted by the named output mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub named_public_inputs: Option<BTreeMap<String, String>>,
+    pub proof: RapidSnarkProof,
 }
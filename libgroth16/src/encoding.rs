@@ -0,0 +1,101 @@
+use crate::utils::ParseError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use num_bigint::BigUint;
+
+fn invalid(message: String) -> anyhow::Error {
+    anyhow::Error::new(ParseError { message })
+}
+
+/// Splits a number into `n_limbs` limbs of `limb_bits` bits, least significant first, as decimal strings. This is how
+/// circom RSA and big integer templates take their inputs.
+pub fn to_limbs(value: &BigUint, limb_bits: usize, n_limbs: usize) -> anyhow::Result<Vec<String>> {
+    if limb_bits == 0 || value.bits() > (limb_bits * n_limbs) as u64 {
+        return Err(invalid(format!(
+            "{} bit number does not fit {} limbs of {} bits",
+            value.bits(),
+            n_limbs,
+            limb_bits
+        )));
+    }
+    let mask = (BigUint::from(1u8) << limb_bits) - 1u8;
+    Ok((0..n_limbs)
+        .map(|i| ((value >> (i * limb_bits)) & &mask).to_string())
+        .collect())
+}
+
+/// Joins decimal limbs of `limb_bits` bits, least significant first.
+pub fn from_limbs(limbs: &[String], limb_bits: usize) -> anyhow::Result<BigUint> {
+    let mut value = BigUint::default();
+    for (i, limb) in limbs.iter().enumerate() {
+        let limb: BigUint = limb
+            .parse()
+            .ok()
+            .filter(|limb: &BigUint| limb.bits() <= limb_bits as u64)
+            .ok_or_else(|| invalid(format!("invalid limb {}", limb)))?;
+        value |= limb << (i * limb_bits);
+    }
+    Ok(value)
+}
+
+/// The bytes of a string as decimal strings, zero-padded to `len`.
+pub fn to_padded_bytes(value: &str, len: usize) -> anyhow::Result<Vec<String>> {
+    if value.len() > len {
+        return Err(invalid(format!("{} bytes do not fit {}", value.len(), len)));
+    }
+    let mut bytes: Vec<String> = value.bytes().map(|b| b.to_string()).collect();
+    bytes.resize(len, "0".to_string());
+    Ok(bytes)
+}
+
+/// Parses decimal strings of byte values, the inverse of [`to_padded_bytes`] without the padding removal.
+pub fn from_bytes(values: &[String]) -> anyhow::Result<Vec<u8>> {
+    values
+        .iter()
+        .map(|value| {
+            value
+                .parse::<u8>()
+                .map_err(|_| invalid(format!("invalid byte {}", value)))
+        })
+        .collect()
+}
+
+/// Decodes base64url, with or without padding, as used by JWTs and JWKs.
+pub fn decode_base64url(value: &str) -> anyhow::Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim().trim_end_matches('='))
+        .map_err(|err| invalid(format!("invalid base64url: {}", err)))
+}
+
+#[cfg(test)]
+mod encoding_test {
+    use crate::encoding::{decode_base64url, from_bytes, from_limbs, to_limbs, to_padded_bytes};
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_limbs() {
+        let value = BigUint::from(0x1234_5678_9abc_u64);
+        let limbs = to_limbs(&value, 16, 4).unwrap();
+        assert_eq!(vec!["39612", "22136", "4660", "0"], limbs);
+        assert_eq!(value, from_limbs(&limbs, 16).unwrap());
+        assert!(to_limbs(&value, 16, 2).is_err());
+        assert!(from_limbs(&["65536".to_string()], 16).is_err());
+        assert!(from_limbs(&["0x12".to_string()], 16).is_err());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let bytes = to_padded_bytes("ab", 4).unwrap();
+        assert_eq!(vec!["97", "98", "0", "0"], bytes);
+        assert_eq!(vec![97, 98, 0, 0], from_bytes(&bytes).unwrap());
+        assert!(to_padded_bytes("abc", 2).is_err());
+        assert!(from_bytes(&["256".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_base64url() {
+        assert_eq!(vec![0xfb, 0xff], decode_base64url("-_8").unwrap());
+        assert_eq!(vec![0xfb, 0xff], decode_base64url("-_8=").unwrap());
+        assert!(decode_base64url("+/8").is_err());
+    }
+}
@@ -0,0 +1,407 @@
+use crate::dto::ProvingOutput;
+use crate::encoding;
+#[cfg(feature = "prover")]
+use crate::utils::ProvingContext;
+use crate::utils::{do_verify, ParseError};
+use anyhow::Context;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Length the guardianhash circuit pads the signed part of the JWT to.
+pub const MAX_JWT_LEN: usize = 512;
+/// The salt is a 32 character string, passed as its ASCII codes.
+pub const SALT_LEN: usize = 32;
+/// RSA numbers are split into 17 little-endian limbs of 121 bits.
+pub const LIMB_BITS: usize = 121;
+pub const N_LIMBS: usize = 17;
+/// The identifier hash is a SHA-256, output as its bytes.
+pub const HASH_LEN: usize = 32;
+
+/// The input of the guardianhash circuit, serialized as the circuit input JSON.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GuardianInput {
+    pub jwt: Vec<String>,
+    pub signature: Vec<String>,
+    pub pubkey: Vec<String>,
+    pub salt: Vec<String>,
+}
+
+impl GuardianInput {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("failed to serialize guardian input")
+    }
+}
+
+/// The public outputs of a guardianhash proof: 32 bytes of identifier hash, the [`N_LIMBS`] limbs of the issuer's
+/// modulus, then the [`SALT_LEN`] bytes of the salt.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GuardianStatement {
+    /// The identifier hash, in hex.
+    pub identifier_hash: String,
+    /// The issuer's RSA modulus as a base64url JWK `n`.
+    pub pubkey: String,
+    pub salt: String,
+}
+
+impl GuardianStatement {
+    /// Decodes `ProvingOutput.public_inputs`.
+    pub fn decode(public_inputs: &[String]) -> anyhow::Result<Self> {
+        if public_inputs.len() != HASH_LEN + N_LIMBS + SALT_LEN {
+            return Err(invalid(&format!(
+                "expected {} public inputs, got {}",
+                HASH_LEN + N_LIMBS + SALT_LEN,
+                public_inputs.len()
+            )));
+        }
+        let (hash, rest) = public_inputs.split_at(HASH_LEN);
+        let (pubkey, salt) = rest.split_at(N_LIMBS);
+        let salt = String::from_utf8(decode_bytes(salt)?)
+            .ok()
+            .filter(|salt| salt.is_ascii())
+            .ok_or_else(|| invalid("salt is not ASCII"))?;
+        Ok(GuardianStatement {
+            identifier_hash: hex::encode(decode_bytes(hash)?),
+            pubkey: URL_SAFE_NO_PAD.encode(from_limbs(pubkey)?.to_bytes_be()),
+            salt,
+        })
+    }
+
+    /// Encodes the statement as `ProvingOutput.public_inputs`.
+    pub fn encode(&self) -> anyhow::Result<Vec<String>> {
+        let hash = hex::decode(&self.identifier_hash)
+            .ok()
+            .filter(|hash| hash.len() == HASH_LEN)
+            .ok_or_else(|| {
+                invalid(&format!(
+                    "identifier hash must be {} bytes of hex",
+                    HASH_LEN
+                ))
+            })?;
+        if self.salt.len() != SALT_LEN || !self.salt.is_ascii() {
+            return Err(invalid(&format!(
+                "salt must be {} ASCII characters",
+                SALT_LEN
+            )));
+        }
+        let mut public_inputs: Vec<String> = hash.iter().map(|b| b.to_string()).collect();
+        public_inputs.extend(to_limbs(&self.modulus()?)?);
+        public_inputs.extend(self.salt.bytes().map(|b| b.to_string()));
+        Ok(public_inputs)
+    }
+
+    /// The issuer's RSA modulus.
+    pub fn modulus(&self) -> anyhow::Result<BigUint> {
+        decode_base64url(&self.pubkey, "pubkey")
+    }
+}
+
+/// A JSON Web Key Set, as published by an issuer. Only the RSA keys are used.
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kty: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default)]
+    n: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuardianVerification {
+    /// Whether the proof passes the pairing check.
+    pub valid: bool,
+    /// The `kid` of the key the proof's modulus belongs to, for a valid proof.
+    pub kid: Option<String>,
+    pub statement: GuardianStatement,
+}
+
+/// The modulus of a valid proof matches no key of the trusted set.
+#[derive(Debug)]
+pub(crate) struct UntrustedKeyError;
+
+impl Display for UntrustedKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no trusted key matches the proof's pubkey")
+    }
+}
+
+impl std::error::Error for UntrustedKeyError {}
+
+/// Verifies a guardianhash proof and checks that its modulus belongs to a key of `jwks`, a JWKS document such as
+/// Google's or Apple's. Keys without a `kid` match with an empty one. Fails with an untrusted key error if the proof
+/// is valid but none of the keys matched.
+pub fn verify_guardian_proof(
+    vk: &str,
+    proving_output: &str,
+    jwks: &str,
+) -> anyhow::Result<GuardianVerification> {
+    let jwks: Jwks = serde_json::from_str(jwks).context("failed to decode JWKS")?;
+    let output: ProvingOutput =
+        serde_json::from_str(proving_output).context("failed to decode ProvingOutput")?;
+    let statement = GuardianStatement::decode(&output.public_inputs)?;
+    if !do_verify(vk, proving_output)? {
+        return Ok(GuardianVerification {
+            valid: false,
+            kid: None,
+            statement,
+        });
+    }
+    let kid = trusted_kid(&jwks, &statement.modulus()?).ok_or(UntrustedKeyError)?;
+    Ok(GuardianVerification {
+        valid: true,
+        kid: Some(kid),
+        statement,
+    })
+}
+
+fn trusted_kid(jwks: &Jwks, modulus: &BigUint) -> Option<String> {
+    jwks.keys
+        .iter()
+        .find(|key| {
+            key.kty == "RSA"
+                && key
+                    .n
+                    .as_deref()
+                    .and_then(|n| decode_base64url(n, "n").ok())
+                    .is_some_and(|n| &n == modulus)
+        })
+        .map(|key| key.kid.clone().unwrap_or_default())
+}
+
+/// Builds a [`GuardianInput`] from an RS256 JWT, the JWK modulus of its signing key and a salt.
+#[derive(Clone, Debug)]
+pub struct GuardianInputBuilder {
+    jwt: String,
+    signature: Option<String>,
+    modulus: String,
+    salt: String,
+    max_jwt_len: usize,
+}
+
+impl GuardianInputBuilder {
+    /// `jwt` is the compact JWT; `modulus` is the base64url `n` of the issuer's JWK.
+    pub fn new(jwt: &str, modulus: &str, salt: &str) -> Self {
+        GuardianInputBuilder {
+            jwt: jwt.trim().to_string(),
+            signature: None,
+            modulus: modulus.trim().to_string(),
+            salt: salt.to_string(),
+            max_jwt_len: MAX_JWT_LEN,
+        }
+    }
+
+    /// The base64url signature, when the JWT is passed without its signature part.
+    pub fn signature(mut self, signature: &str) -> Self {
+        self.signature = Some(signature.trim().to_string());
+        self
+    }
+
+    /// Overrides [`MAX_JWT_LEN`], for circuits compiled with another JWT length.
+    pub fn max_jwt_len(mut self, max_jwt_len: usize) -> Self {
+        self.max_jwt_len = max_jwt_len;
+        self
+    }
+
+    /// Takes the JWT length from the circuit's `jwt` input, if the context has symbols loaded.
+    #[cfg(feature = "prover")]
+    pub fn for_context(self, ctx: &ProvingContext) -> Self {
+        let jwt_len = ctx
+            .input_schema()
+            .ok()
+            .and_then(|schema| schema.into_iter().find(|signal| signal.name == "jwt"))
+            .map(|signal| signal.size());
+        match jwt_len {
+            Some(jwt_len) => self.max_jwt_len(jwt_len),
+            None => self,
+        }
+    }
+
+    pub fn build(&self) -> anyhow::Result<GuardianInput> {
+        let parts: Vec<&str> = self.jwt.split('.').collect();
+        let (signed, jwt_signature) = match parts[..] {
+            [header, payload] => (&self.jwt[..header.len() + payload.len() + 1], None),
+            [header, payload, signature] => (
+                &self.jwt[..header.len() + payload.len() + 1],
+                Some(signature),
+            ),
+            _ => return Err(invalid("expected a compact JWT")),
+        };
+        let signature = match (self.signature.as_deref(), jwt_signature) {
+            (Some(signature), Some(jwt_signature)) if signature != jwt_signature => {
+                return Err(invalid("signature does not match the JWT's signature"))
+            }
+            (Some(signature), _) | (None, Some(signature)) => signature,
+            (None, None) => return Err(invalid("missing signature")),
+        };
+        if signed.len() > self.max_jwt_len {
+            return Err(invalid(&format!(
+                "signed part of the JWT is {} bytes, the circuit takes at most {}",
+                signed.len(),
+                self.max_jwt_len
+            )));
+        }
+        if self.salt.len() != SALT_LEN || !self.salt.is_ascii() {
+            return Err(invalid(&format!(
+                "salt must be {} ASCII characters",
+                SALT_LEN
+            )));
+        }
+
+        let modulus = decode_base64url(&self.modulus, "modulus")?;
+        let signature = decode_base64url(signature, "signature")?;
+        if signature >= modulus {
+            return Err(invalid("signature is not smaller than the modulus"));
+        }
+
+        Ok(GuardianInput {
+            jwt: encoding::to_padded_bytes(signed, self.max_jwt_len)?,
+            signature: to_limbs(&signature)?,
+            pubkey: to_limbs(&modulus)?,
+            salt: self.salt.bytes().map(|b| b.to_string()).collect(),
+        })
+    }
+}
+
+fn invalid(message: &str) -> anyhow::Error {
+    anyhow::Error::new(ParseError {
+        message: message.to_string(),
+    })
+    .context("invalid guardian input")
+}
+
+fn decode_base64url(value: &str, what: &str) -> anyhow::Result<BigUint> {
+    let bytes = encoding::decode_base64url(value)
+        .with_context(|| format!("invalid {}", what))
+        .context("invalid guardian input")?;
+    Ok(BigUint::from_bytes_be(&bytes))
+}
+
+fn to_limbs(value: &BigUint) -> anyhow::Result<Vec<String>> {
+    encoding::to_limbs(value, LIMB_BITS, N_LIMBS).context("invalid guardian input")
+}
+
+fn from_limbs(limbs: &[String]) -> anyhow::Result<BigUint> {
+    encoding::from_limbs(limbs, LIMB_BITS).context("invalid guardian input")
+}
+
+fn decode_bytes(values: &[String]) -> anyhow::Result<Vec<u8>> {
+    encoding::from_bytes(values).context("invalid guardian input")
+}
+
+#[cfg(test)]
+pub(crate) mod guardian_test {
+    use crate::guardian::{
+        decode_base64url, trusted_kid, GuardianInputBuilder, GuardianStatement, Jwks, MAX_JWT_LEN,
+        N_LIMBS,
+    };
+    use crate::utils::{failure_cause, FailureCause};
+
+    // The example of the circuit fixtures in tests.rs.
+    const JWT: &str = "eyJhbGciOiJSUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiYWRtaW4iOnRydWUsImlhdCI6MTUxNjIzOTAyMn0";
+    const SIGNATURE: &str = "NHVaYe26MbtOYhSKkoKYdFVomg4i8ZJd8_-RU8VNbftc4TSMb4bXP3l3YlNWACwyXPGffz5aXHc6lty1Y2t4SWRqGteragsVdZufDn5BlnJl9pdR_kdVFUsra2rWKEofkZeIC4yWytE58sMIihvo9H1ScmmVwBcQP6XETqYd0aSHp1gOa9RdUPDvoXQ5oqygTqVtxaDr6wUFKrKItgBMzWIdNZ6y7O9E0DhEPTbE9rfBo6KTFsHAZnMg4k68CDp2woYIaXbmYTWcvbzIuHO7_37GT79XdIwkm95QJ7hYC9RiwrV7mesbY4PAahERJawntho0my942XheVLmGwLMBkQ";
+    const MODULUS: &str = "u1SU1LfVLPHCozMxH2Mo4lgOEePzNm0tRgeLezV6ffAt0gunVTLw7onLRnrq0_IzW7yWR7QkrmBL7jTKEn5u-qKhbwKfBstIs-bMY2Zkp18gnTxKLxoS2tFczGkPLPgizskuemMghRniWaoLcyehkd3qqGElvW_VDL5AaWTg0nLVkjRo9z-40RQzuVaE8AkAFmxZzow3x-VJYKdjykkJ0iT9wCS0DRTXu269V264Vf_3jvredZiKRkgwlL9xNAwxXFg0x_XFw005UWVRIkdgcKWTjpBP2dPwVZ4WWC-9aGVd-Gyn1o0CLelf4rEjGoXbAAEgAqeGUxrcIlbjXfbcmw";
+    const SALT: &str = "a677999396dc49a28ad6c9c242719bb3";
+
+    /// The input of the example, for tests that prove.
+    pub(crate) fn example_input() -> String {
+        GuardianInputBuilder::new(JWT, MODULUS, SALT)
+            .signature(SIGNATURE)
+            .build()
+            .unwrap()
+            .to_json()
+    }
+
+    #[test]
+    fn test_build_input() {
+        let input = GuardianInputBuilder::new(&format!("{}.{}", JWT, SIGNATURE), MODULUS, SALT)
+            .build()
+            .unwrap();
+        assert_eq!(MAX_JWT_LEN, input.jwt.len());
+        assert_eq!(["101", "121", "74"], input.jwt[..3]);
+        assert_eq!("0", input.jwt[JWT.len()]);
+        assert_eq!(N_LIMBS, input.signature.len());
+        assert_eq!("136066698678378650066472176144548241", input.signature[0]);
+        assert_eq!("1063982966443379747600844439851650", input.signature[16]);
+        assert_eq!("5841544268561861499519250994748571", input.pubkey[0]);
+        assert_eq!("3799511822475913352444008446631779", input.pubkey[16]);
+        assert_eq!(["97", "54", "55"], input.salt[..3]);
+
+        let detached = GuardianInputBuilder::new(JWT, MODULUS, SALT)
+            .signature(SIGNATURE)
+            .build()
+            .unwrap();
+        assert_eq!(input, detached);
+    }
+
+    #[test]
+    fn test_build_input_checks() {
+        let err = GuardianInputBuilder::new(JWT, MODULUS, SALT)
+            .signature(SIGNATURE)
+            .max_jwt_len(64)
+            .build()
+            .unwrap_err();
+        assert_eq!(FailureCause::InvalidInput, failure_cause(&err));
+        assert!(GuardianInputBuilder::new(JWT, MODULUS, SALT)
+            .build()
+            .is_err());
+        assert!(GuardianInputBuilder::new(JWT, MODULUS, "short")
+            .signature(SIGNATURE)
+            .build()
+            .is_err());
+        assert!(GuardianInputBuilder::new(JWT, SIGNATURE, SALT)
+            .signature(MODULUS)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_decode_statement() {
+        let mut public_inputs: Vec<String> = (0..32).map(|b: u8| (b * 7).to_string()).collect();
+        public_inputs.extend(
+            GuardianInputBuilder::new(JWT, MODULUS, SALT)
+                .signature(SIGNATURE)
+                .build()
+                .unwrap()
+                .pubkey,
+        );
+        public_inputs.extend(SALT.bytes().map(|b| b.to_string()));
+
+        let statement = GuardianStatement::decode(&public_inputs).unwrap();
+        assert_eq!(
+            "00070e151c232a31383f464d545b626970777e858c939aa1a8afb6bdc4cbd2d9",
+            statement.identifier_hash
+        );
+        assert_eq!(MODULUS, statement.pubkey);
+        assert_eq!(SALT, statement.salt);
+        assert_eq!(2048, statement.modulus().unwrap().bits());
+        assert_eq!(public_inputs, statement.encode().unwrap());
+
+        public_inputs[0] = "256".to_string();
+        assert!(GuardianStatement::decode(&public_inputs).is_err());
+        assert!(GuardianStatement::decode(&public_inputs[1..]).is_err());
+    }
+
+    #[test]
+    fn test_trusted_kid() {
+        let jwks: Jwks = serde_json::from_str(&format!(
+            r#"{{"keys": [
+                {{"kty": "EC", "kid": "ec", "crv": "P-256", "x": "", "y": ""}},
+                {{"kty": "RSA", "kid": "other", "n": "{}", "e": "AQAB"}},
+                {{"kty": "RSA", "kid": "2023-11", "n": "AAAA{}", "e": "AQAB"}}
+            ]}}"#,
+            SIGNATURE, MODULUS
+        ))
+        .unwrap();
+        let modulus = decode_base64url(MODULUS, "n").unwrap();
+        assert_eq!(Some("2023-11".to_string()), trusted_kid(&jwks, &modulus));
+        let other = decode_base64url(SALT, "n").unwrap();
+        assert_eq!(None, trusted_kid(&jwks, &other));
+    }
+}
@@ -0,0 +1,349 @@
+//! Handle-based C API, for hosts that wrap native resources in handles (C# `SafeHandle`). Strings are passed as UTF-8
+//! byte spans with a length, and outputs are returned in buffers the library allocates and [`groth16_buffer_release`]
+//! frees. Every fallible call returns a status, the numbering of the gRPC `FailureCause`, and leaves the error message
+//! in its `error` buffer. `bindings/csharp/Groth16.Native.cs` declares these functions for C#, see the test below.
+//!
+//! All functions share one safety contract: pointers are null or valid for their length, and handles and buffers come
+//! from this library and are released once.
+#![allow(clippy::missing_safety_doc)]
+use crate::utils::{
+    do_prove, do_verify, failure_cause, load_context, serialize, FailureCause, ParseError,
+    ProvingContext,
+};
+use std::ptr;
+
+pub const GROTH16_OK: i32 = 0;
+pub const GROTH16_INVALID_ARTIFACT: i32 = 1;
+pub const GROTH16_INVALID_INPUT: i32 = 2;
+pub const GROTH16_INVALID_VERIFYING_KEY: i32 = 3;
+pub const GROTH16_WITNESS_GENERATION: i32 = 4;
+pub const GROTH16_UNSATISFIED_CONSTRAINTS: i32 = 5;
+pub const GROTH16_PROVING: i32 = 6;
+pub const GROTH16_UNKNOWN_CIRCUIT: i32 = 7;
+pub const GROTH16_INTERNAL: i32 = 8;
+pub const GROTH16_UNTRUSTED_KEY: i32 = 9;
+pub const GROTH16_CANCELLED: i32 = 10;
+pub const GROTH16_TIMED_OUT: i32 = 11;
+
+/// A proving context behind a handle.
+pub struct Groth16Context {
+    ctx: ProvingContext,
+}
+
+/// Bytes owned by the library. Empty buffers have a null `data`.
+#[repr(C)]
+pub struct Groth16Buffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl Groth16Buffer {
+    fn empty() -> Self {
+        Groth16Buffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn from_string(value: String) -> Self {
+        let bytes = value.into_bytes().into_boxed_slice();
+        let len = bytes.len();
+        Groth16Buffer {
+            data: Box::into_raw(bytes) as *mut u8,
+            len,
+        }
+    }
+}
+
+fn status(err: &anyhow::Error) -> i32 {
+    match failure_cause(err) {
+        FailureCause::InvalidArtifact => GROTH16_INVALID_ARTIFACT,
+        FailureCause::InvalidInput => GROTH16_INVALID_INPUT,
+        FailureCause::InvalidVerifyingKey => GROTH16_INVALID_VERIFYING_KEY,
+        FailureCause::WitnessGeneration => GROTH16_WITNESS_GENERATION,
+        FailureCause::UnsatisfiedConstraints => GROTH16_UNSATISFIED_CONSTRAINTS,
+        FailureCause::Proving => GROTH16_PROVING,
+        FailureCause::UnknownCircuit => GROTH16_UNKNOWN_CIRCUIT,
+        FailureCause::UntrustedKey => GROTH16_UNTRUSTED_KEY,
+        FailureCause::Internal => GROTH16_INTERNAL,
+        FailureCause::Cancelled => GROTH16_CANCELLED,
+        FailureCause::TimedOut => GROTH16_TIMED_OUT,
+    }
+}
+
+/// Stores the result of a call: the value through `write` on success, the message in `error` on failure. `error` is
+/// always written, as hosts read it as an out parameter; it may be null.
+unsafe fn finish<T>(
+    result: anyhow::Result<T>,
+    error: *mut Groth16Buffer,
+    write: impl FnOnce(T),
+) -> i32 {
+    let (status, message) = match result {
+        Ok(value) => {
+            write(value);
+            (GROTH16_OK, Groth16Buffer::empty())
+        }
+        Err(err) => (
+            status(&err),
+            Groth16Buffer::from_string(format!("{:#}", err)),
+        ),
+    };
+    match error.as_mut() {
+        Some(error) => *error = message,
+        None => groth16_buffer_release(message),
+    }
+    status
+}
+
+fn invalid_argument(name: &str, reason: &str) -> anyhow::Error {
+    anyhow::Error::new(ParseError {
+        message: format!("{} {}", name, reason),
+    })
+}
+
+unsafe fn utf8<'a>(data: *const u8, len: usize, name: &str) -> anyhow::Result<&'a str> {
+    if data.is_null() {
+        return match len {
+            0 => Ok(""),
+            _ => Err(invalid_argument(name, "is null")),
+        };
+    }
+    std::str::from_utf8(std::slice::from_raw_parts(data, len))
+        .map_err(|_| invalid_argument(name, "is not valid UTF-8"))
+}
+
+unsafe fn context<'a>(ctx: *const Groth16Context) -> anyhow::Result<&'a ProvingContext> {
+    ctx.as_ref()
+        .map(|handle| &handle.ctx)
+        .ok_or_else(|| invalid_argument("ctx", "is null"))
+}
+
+/// Checks an out parameter and clears it, so that it is empty unless the call succeeds.
+unsafe fn out<'a, T>(ptr: *mut T, name: &str, empty: T) -> anyhow::Result<&'a mut T> {
+    let out = ptr
+        .as_mut()
+        .ok_or_else(|| invalid_argument(name, "is null"))?;
+    *out = empty;
+    Ok(out)
+}
+
+/// Loads a circuit and stores its handle in `ctx`. The paths are UTF-8 byte spans.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_context_create(
+    wasm_path: *const u8,
+    wasm_path_len: usize,
+    r1cs_path: *const u8,
+    r1cs_path_len: usize,
+    zkey_path: *const u8,
+    zkey_path_len: usize,
+    ctx: *mut *mut Groth16Context,
+    error: *mut Groth16Buffer,
+) -> i32 {
+    let result = (|| {
+        let ctx = out(ctx, "ctx", ptr::null_mut())?;
+        let wasm_path = utf8(wasm_path, wasm_path_len, "wasm_path")?;
+        let r1cs_path = utf8(r1cs_path, r1cs_path_len, "r1cs_path")?;
+        let zkey_path = utf8(zkey_path, zkey_path_len, "zkey_path")?;
+        Ok((ctx, load_context(wasm_path, r1cs_path, zkey_path)?))
+    })();
+    finish(result, error, |(ctx, loaded)| {
+        *ctx = Box::into_raw(Box::new(Groth16Context { ctx: loaded }))
+    })
+}
+
+/// Frees a context. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_context_destroy(ctx: *mut Groth16Context) {
+    if !ctx.is_null() {
+        drop(Box::from_raw(ctx));
+    }
+}
+
+/// Stores the verifying key of a context, in compressed hex, in `output`.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_context_verifying_key(
+    ctx: *const Groth16Context,
+    output: *mut Groth16Buffer,
+    error: *mut Groth16Buffer,
+) -> i32 {
+    let result = (|| {
+        let output = out(output, "output", Groth16Buffer::empty())?;
+        Ok((output, context(ctx)?.verifying_key_in_hex()))
+    })();
+    finish(result, error, |(output, vk)| {
+        *output = Groth16Buffer::from_string(vk)
+    })
+}
+
+/// Proves a circuit input JSON and stores the proving output JSON in `output`.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_context_prove(
+    ctx: *const Groth16Context,
+    input: *const u8,
+    input_len: usize,
+    output: *mut Groth16Buffer,
+    error: *mut Groth16Buffer,
+) -> i32 {
+    let result = (|| {
+        let output = out(output, "output", Groth16Buffer::empty())?;
+        let ctx = context(ctx)?;
+        let input = utf8(input, input_len, "input")?;
+        let (pub_inputs, proof) = do_prove(ctx, input)?;
+        Ok((output, serialize(pub_inputs, proof)?))
+    })();
+    finish(result, error, |(output, proving_output)| {
+        *output = Groth16Buffer::from_string(proving_output)
+    })
+}
+
+/// Verifies a proving output JSON against a verifying key and stores the result in `valid`.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_verify(
+    vk: *const u8,
+    vk_len: usize,
+    proving_output: *const u8,
+    proving_output_len: usize,
+    valid: *mut bool,
+    error: *mut Groth16Buffer,
+) -> i32 {
+    let result = (|| {
+        let valid = out(valid, "valid", false)?;
+        let vk = utf8(vk, vk_len, "vk")?;
+        let proving_output = utf8(proving_output, proving_output_len, "proving_output")?;
+        Ok((valid, do_verify(vk, proving_output)?))
+    })();
+    finish(result, error, |(valid, result)| *valid = result)
+}
+
+/// Frees a buffer returned by the library. Empty buffers are ignored.
+#[no_mangle]
+pub unsafe extern "C" fn groth16_buffer_release(buffer: Groth16Buffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+#[cfg(test)]
+mod handle_test {
+    use crate::handle::{
+        groth16_buffer_release, groth16_context_prove, groth16_verify, Groth16Buffer,
+        GROTH16_CANCELLED, GROTH16_INTERNAL, GROTH16_INVALID_ARTIFACT, GROTH16_INVALID_INPUT,
+        GROTH16_INVALID_VERIFYING_KEY, GROTH16_OK, GROTH16_PROVING, GROTH16_TIMED_OUT,
+        GROTH16_UNKNOWN_CIRCUIT, GROTH16_UNSATISFIED_CONSTRAINTS, GROTH16_UNTRUSTED_KEY,
+        GROTH16_WITNESS_GENERATION,
+    };
+    use std::ptr;
+
+    unsafe fn take(buffer: Groth16Buffer) -> String {
+        let value = String::from_utf8_lossy(std::slice::from_raw_parts(buffer.data, buffer.len))
+            .into_owned();
+        groth16_buffer_release(buffer);
+        value
+    }
+
+    #[test]
+    fn test_errors() {
+        unsafe {
+            let mut valid = false;
+            let mut error = Groth16Buffer::empty();
+            let vk = b"zz";
+            let output = br#"{"public_inputs":[],"proof":{}}"#;
+            let status = groth16_verify(
+                vk.as_ptr(),
+                vk.len(),
+                output.as_ptr(),
+                output.len(),
+                &mut valid,
+                &mut error,
+            );
+            assert_eq!(GROTH16_INVALID_VERIFYING_KEY, status);
+            assert!(take(error).starts_with("failed to decode VerifyingKey"));
+
+            let invalid = [0xffu8, 0xfe];
+            let status = groth16_verify(
+                vk.as_ptr(),
+                vk.len(),
+                invalid.as_ptr(),
+                invalid.len(),
+                &mut valid,
+                ptr::null_mut(),
+            );
+            assert_eq!(GROTH16_INVALID_INPUT, status);
+
+            let mut output = Groth16Buffer::empty();
+            let mut error = Groth16Buffer::empty();
+            let status =
+                groth16_context_prove(ptr::null(), ptr::null(), 0, &mut output, &mut error);
+            assert_eq!(GROTH16_INVALID_INPUT, status);
+            assert!(output.data.is_null());
+            assert!(take(error).contains("ctx"));
+        }
+    }
+
+    /// The exports of this module, in the order `bindings/csharp/Groth16.Native.cs` declares them.
+    const EXPORTS: &[&str] = &[
+        "groth16_context_create",
+        "groth16_context_destroy",
+        "groth16_context_verifying_key",
+        "groth16_context_prove",
+        "groth16_verify",
+        "groth16_buffer_release",
+    ];
+
+    /// The members of the C# `Groth16Status` enum, in order.
+    const STATUSES: &[(&str, i32)] = &[
+        ("Ok", GROTH16_OK),
+        ("InvalidArtifact", GROTH16_INVALID_ARTIFACT),
+        ("InvalidInput", GROTH16_INVALID_INPUT),
+        ("InvalidVerifyingKey", GROTH16_INVALID_VERIFYING_KEY),
+        ("WitnessGeneration", GROTH16_WITNESS_GENERATION),
+        ("UnsatisfiedConstraints", GROTH16_UNSATISFIED_CONSTRAINTS),
+        ("Proving", GROTH16_PROVING),
+        ("UnknownCircuit", GROTH16_UNKNOWN_CIRCUIT),
+        ("Internal", GROTH16_INTERNAL),
+        ("UntrustedKey", GROTH16_UNTRUSTED_KEY),
+        ("Cancelled", GROTH16_CANCELLED),
+        ("TimedOut", GROTH16_TIMED_OUT),
+    ];
+
+    /// The C# declarations are written by hand; this checks them against the exports of the generated C header.
+    #[test]
+    fn test_csharp_bindings() {
+        let mut header = Vec::new();
+        cbindgen::generate(env!("CARGO_MANIFEST_DIR"))
+            .unwrap()
+            .write(&mut header);
+        let header = String::from_utf8(header).unwrap();
+        for name in EXPORTS {
+            assert!(
+                header.contains(&format!(" {}(", name)),
+                "{} is not in the C header",
+                name
+            );
+        }
+
+        let bindings = include_str!("../bindings/csharp/Groth16.Native.cs");
+        let declared: Vec<&str> = bindings
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("internal static extern "))
+            .map(|rest| rest[..rest.find('(').unwrap()].rsplit(' ').next().unwrap())
+            .collect();
+        assert_eq!(EXPORTS, declared.as_slice());
+
+        let statuses: Vec<String> = STATUSES
+            .iter()
+            .map(|(name, value)| format!("{} = {},", name, value))
+            .collect();
+        let enum_start = bindings.find("public enum Groth16Status").unwrap();
+        let declared: Vec<&str> = bindings[enum_start..]
+            .lines()
+            .skip(2)
+            .map(str::trim)
+            .take_while(|line| *line != "}")
+            .collect();
+        assert_eq!(statuses, declared);
+    }
+}
@@ -0,0 +1,157 @@
+//! JNI exports for `io.portkey.groth16.Groth16Prover` and `Groth16Verifier`, see `java/`. A prover holds its context as
+//! a `long` handle from `load` until `free`; errors are thrown as `Groth16Exception`s carrying their failure cause.
+#![allow(non_snake_case)]
+
+use crate::utils::{
+    do_prove, do_verify, failure_cause, load_context, serialize, FailureCause, ParseError,
+    ProvingContext,
+};
+use jni::objects::{JClass, JString, JThrowable, JValue};
+use jni::sys::{jboolean, jlong, jstring, JNI_FALSE};
+use jni::JNIEnv;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+const EXCEPTION_CLASS: &str = "io/portkey/groth16/Groth16Exception";
+
+/// The name of the matching `Groth16Exception.Cause` constant.
+fn cause_name(cause: FailureCause) -> &'static str {
+    match cause {
+        FailureCause::InvalidArtifact => "INVALID_ARTIFACT",
+        FailureCause::InvalidInput => "INVALID_INPUT",
+        FailureCause::InvalidVerifyingKey => "INVALID_VERIFYING_KEY",
+        FailureCause::WitnessGeneration => "WITNESS_GENERATION",
+        FailureCause::UnsatisfiedConstraints => "UNSATISFIED_CONSTRAINTS",
+        FailureCause::Proving => "PROVING",
+        FailureCause::UnknownCircuit => "UNKNOWN_CIRCUIT",
+        FailureCause::UntrustedKey => "UNTRUSTED_KEY",
+        FailureCause::Internal => "INTERNAL",
+        FailureCause::Cancelled => "CANCELLED",
+        FailureCause::TimedOut => "TIMED_OUT",
+    }
+}
+
+fn throw(env: &mut JNIEnv, err: anyhow::Error) {
+    let message = format!("{:#}", err);
+    let thrown = (|| {
+        let cause = env.new_string(cause_name(failure_cause(&err)))?;
+        let message = env.new_string(&message)?;
+        let exception = env.new_object(
+            EXCEPTION_CLASS,
+            "(Ljava/lang/String;Ljava/lang/String;)V",
+            &[JValue::Object(&cause), JValue::Object(&message)],
+        )?;
+        env.throw(JThrowable::from(exception))
+    })();
+    // Creating the exception fails if the class is missing or another exception is pending.
+    if thrown.is_err() && !env.exception_check().unwrap_or(false) {
+        let _ = env.throw_new("java/lang/RuntimeException", message);
+    }
+}
+
+/// Runs `f`, throwing its error and returning `default` to the JVM instead. A panic must not unwind into the JVM, so it
+/// is thrown as an `INTERNAL` failure.
+fn run<T>(env: &mut JNIEnv, default: T, f: impl FnOnce(&mut JNIEnv) -> anyhow::Result<T>) -> T {
+    let result = catch_unwind(AssertUnwindSafe(|| f(env))).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(anyhow::anyhow!("native code panicked: {}", message))
+    });
+    match result {
+        Ok(value) => value,
+        Err(err) => {
+            throw(env, err);
+            default
+        }
+    }
+}
+
+fn java_string(env: &mut JNIEnv, value: &JString, name: &str) -> anyhow::Result<String> {
+    env.get_string(value).map(String::from).map_err(|_| {
+        anyhow::Error::new(ParseError {
+            message: format!("{} is null", name),
+        })
+    })
+}
+
+fn new_string(env: &mut JNIEnv, value: String) -> anyhow::Result<jstring> {
+    Ok(env.new_string(value)?.into_raw())
+}
+
+/// The handle is checked for zero on the Java side, which also keeps it alive while a call runs.
+unsafe fn context<'a>(handle: jlong) -> &'a ProvingContext {
+    &*(handle as *const ProvingContext)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_portkey_groth16_Groth16Prover_load(
+    mut env: JNIEnv,
+    _class: JClass,
+    wasm_path: JString,
+    r1cs_path: JString,
+    zkey_path: JString,
+) -> jlong {
+    run(&mut env, 0, |env| {
+        let wasm_path = java_string(env, &wasm_path, "wasmPath")?;
+        let r1cs_path = java_string(env, &r1cs_path, "r1csPath")?;
+        let zkey_path = java_string(env, &zkey_path, "zkeyPath")?;
+        let ctx = load_context(&wasm_path, &r1cs_path, &zkey_path)?;
+        Ok(Box::into_raw(Box::new(ctx)) as jlong)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_portkey_groth16_Groth16Prover_prove(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    input: JString,
+) -> jstring {
+    run(&mut env, ptr::null_mut(), |env| {
+        let input = java_string(env, &input, "input")?;
+        let (pub_inputs, proof) = do_prove(context(handle), &input)?;
+        new_string(env, serialize(pub_inputs, proof)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_portkey_groth16_Groth16Prover_verifyingKey(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) -> jstring {
+    run(&mut env, ptr::null_mut(), |env| {
+        new_string(env, context(handle).verifying_key_in_hex())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_io_portkey_groth16_Groth16Prover_free(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) {
+    run(&mut env, (), |_| {
+        if handle != 0 {
+            drop(Box::from_raw(handle as *mut ProvingContext));
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_portkey_groth16_Groth16Verifier_verify(
+    mut env: JNIEnv,
+    _class: JClass,
+    vk: JString,
+    proving_output: JString,
+) -> jboolean {
+    run(&mut env, JNI_FALSE, |env| {
+        let vk = java_string(env, &vk, "vk")?;
+        let proving_output = java_string(env, &proving_output, "provingOutput")?;
+        Ok(do_verify(&vk, &proving_output)? as jboolean)
+    })
+}
@@ -0,0 +1,341 @@
+//! Asynchronous proving for hosts that should not block a thread per proof. `prove_async_bn254` queues a job on a pool
+//! of worker threads and returns its id; the job calls back with the proving output once it finished. A job holds the
+//! artifacts of its context, so the context may be reloaded or freed while jobs are queued.
+//!
+//! `ctx` and `input` are null or valid, and `user_data` may be used from any thread.
+#![allow(clippy::missing_safety_doc)]
+use crate::cancel::CancelToken;
+use crate::circuit::SymbolTable;
+use crate::utils::{
+    failure_cause, prove_phases, serialize, FailureCause, ProvingArtifacts, ProvingContext,
+};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+
+pub const GROTH16_JOB_UNKNOWN: i32 = 0;
+pub const GROTH16_JOB_QUEUED: i32 = 1;
+pub const GROTH16_JOB_RUNNING: i32 = 2;
+
+/// Called on a worker thread when a job finished, with its id, a status and `user_data`. The status is 0 with the
+/// proving output in `output`, otherwise the error message is in `output` and the status is -3 if the job was
+/// cancelled, -4 if it timed out, and -1 for any other failure, as `prove_cancellable_bn254` returns. `output` is only
+/// valid during the call.
+pub type Groth16JobCallback = Option<
+    unsafe extern "C" fn(
+        job_id: u64,
+        status: cty::c_int,
+        output: *const cty::c_char,
+        user_data: *mut cty::c_void,
+    ),
+>;
+
+/// The host's `user_data`, which it promises to be usable from the worker threads.
+struct UserData(*mut cty::c_void);
+
+unsafe impl Send for UserData {}
+
+struct Job {
+    id: u64,
+    artifacts: Arc<ProvingArtifacts>,
+    symbols: Option<Arc<SymbolTable>>,
+    input: String,
+    token: Arc<CancelToken>,
+    callback: unsafe extern "C" fn(u64, cty::c_int, *const cty::c_char, *mut cty::c_void),
+    user_data: UserData,
+}
+
+struct JobState {
+    running: bool,
+    token: Arc<CancelToken>,
+}
+
+/// Proofs are parallel themselves, so a few workers already keep every core busy.
+static WORKER_COUNT: AtomicUsize = AtomicUsize::new(2);
+/// Jobs waiting for a worker, beyond which `prove_async_bn254` refuses new ones.
+const QUEUE_CAPACITY: usize = 64;
+static POOL: OnceLock<SyncSender<Job>> = OnceLock::new();
+static JOBS: OnceLock<Mutex<HashMap<u64, JobState>>> = OnceLock::new();
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn jobs() -> std::sync::MutexGuard<'static, HashMap<u64, JobState>> {
+    JOBS.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Without any worker the receiver is dropped, and submitting fails.
+fn pool() -> &'static SyncSender<Job> {
+    POOL.get_or_init(|| {
+        let (sender, receiver) = sync_channel::<Job>(QUEUE_CAPACITY);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..WORKER_COUNT.load(Ordering::Relaxed) {
+            let receiver = receiver.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("groth16-worker-{}", i))
+                .spawn(move || work(&receiver));
+            if let Err(err) = spawned {
+                log::error!("failed to spawn a proving worker: {}", err);
+            }
+        }
+        sender
+    })
+}
+
+fn work(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = match receiver.lock().unwrap_or_else(|e| e.into_inner()).recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+        run(job);
+    }
+}
+
+fn run(job: Job) {
+    if let Some(state) = jobs().get_mut(&job.id) {
+        state.running = true;
+    }
+    // A job that was cancelled or timed out while queued is skipped.
+    let result = job.token.check().and_then(|()| {
+        // The witness calculator may panic on inputs it does not expect; that must not take the worker down.
+        catch_unwind(AssertUnwindSafe(|| {
+            let (pub_inputs, proof, _) = prove_phases(
+                &job.artifacts,
+                job.symbols.as_deref(),
+                &job.input,
+                Some(&job.token),
+            )?;
+            serialize(pub_inputs, proof)
+        }))
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("proving panicked")))
+    });
+
+    let (status, output) = match result {
+        Ok(output) => (0, output),
+        Err(err) => match failure_cause(&err) {
+            FailureCause::Cancelled => (-3, format!("{:#}", err)),
+            FailureCause::TimedOut => (-4, format!("{:#}", err)),
+            _ => {
+                log::warn!("failed to prove job {}: {:#}", job.id, err);
+                (-1, format!("{:#}", err))
+            }
+        },
+    };
+    let output = CString::new(output.replace('\0', " ")).expect("NUL bytes were replaced");
+    unsafe { (job.callback)(job.id, status, output.as_ptr(), job.user_data.0) };
+    // Only now, so that the job is reported as running until its result was delivered.
+    jobs().remove(&job.id);
+}
+
+/// Sets the number of worker threads, 2 by default. Returns -1 once the first job was submitted, or for 0 workers.
+#[no_mangle]
+pub extern "C" fn groth16_set_worker_count(count: usize) -> cty::c_int {
+    if count == 0 || POOL.get().is_some() {
+        return -1;
+    }
+    WORKER_COUNT.store(count, Ordering::Relaxed);
+    0
+}
+
+/// Queues a proof of `input` and returns its job id, or 0 if an argument is invalid or 64 jobs are already waiting for
+/// a worker. Unless it is 0, the job times out `timeout_ms` after it was queued, waiting in the queue included.
+/// `callback` is called exactly once for every job that was queued.
+#[no_mangle]
+pub unsafe extern "C" fn prove_async_bn254(
+    ctx: Option<&ProvingContext>,
+    input: *const cty::c_char,
+    timeout_ms: u64,
+    callback: Groth16JobCallback,
+    user_data: *mut cty::c_void,
+) -> u64 {
+    let input = match input.as_ref().map(|input| CStr::from_ptr(input).to_str()) {
+        Some(Ok(input)) => input.to_owned(),
+        _ => return 0,
+    };
+    let (Some(ctx), Some(callback)) = (ctx, callback) else {
+        return 0;
+    };
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let token = Arc::new(CancelToken::with_timeout_ms(timeout_ms));
+    jobs().insert(
+        id,
+        JobState {
+            running: false,
+            token: token.clone(),
+        },
+    );
+    let job = Job {
+        id,
+        artifacts: ctx.artifacts(),
+        symbols: ctx.symbols(),
+        input,
+        token,
+        callback,
+        user_data: UserData(user_data),
+    };
+    match pool().try_send(job) {
+        Ok(()) => id,
+        Err(err) => {
+            log::warn!("failed to queue job {}: {}", id, err);
+            jobs().remove(&id);
+            0
+        }
+    }
+}
+
+/// Returns `GROTH16_JOB_QUEUED`, `GROTH16_JOB_RUNNING` until the job's callback returned, or `GROTH16_JOB_UNKNOWN`.
+#[no_mangle]
+pub extern "C" fn job_status_bn254(job_id: u64) -> cty::c_int {
+    match jobs().get(&job_id) {
+        Some(state) if state.running => GROTH16_JOB_RUNNING,
+        Some(_) => GROTH16_JOB_QUEUED,
+        None => GROTH16_JOB_UNKNOWN,
+    }
+}
+
+/// Cancels a job: a queued job is skipped and a running one stops between its phases, see `prove_cancellable_bn254`.
+/// The callback is still called, with status -3 unless the proof completed first. Returns -1 if the job is unknown.
+#[no_mangle]
+pub extern "C" fn cancel_job_bn254(job_id: u64) -> cty::c_int {
+    match jobs().get(&job_id) {
+        Some(state) => {
+            state.token.cancel();
+            0
+        }
+        None => -1,
+    }
+}
+
+#[cfg(test)]
+mod jobs_test {
+    use crate::guardian::guardian_test::example_input;
+    use crate::jobs::{
+        cancel_job_bn254, job_status_bn254, prove_async_bn254, GROTH16_JOB_QUEUED,
+        GROTH16_JOB_RUNNING, GROTH16_JOB_UNKNOWN, WORKER_COUNT,
+    };
+    use crate::utils::{do_verify, load_context, ProvingContext};
+    use std::collections::HashMap;
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+    use std::sync::atomic::Ordering;
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    /// A job's status and output as delivered, and its `job_status_bn254` during the callback.
+    type Delivery = (cty::c_int, String, cty::c_int);
+
+    unsafe extern "C" fn ignore(_: u64, _: cty::c_int, _: *const cty::c_char, _: *mut cty::c_void) {
+    }
+
+    unsafe extern "C" fn deliver(
+        job_id: u64,
+        status: cty::c_int,
+        output: *const cty::c_char,
+        user_data: *mut cty::c_void,
+    ) {
+        let sender = &*(user_data as *const Sender<(u64, Delivery)>);
+        let output = CStr::from_ptr(output).to_string_lossy().into_owned();
+        let _ = sender.send((job_id, (status, output, job_status_bn254(job_id))));
+    }
+
+    fn load() -> ProvingContext {
+        load_context(
+            "../data-files/guardianhash.wasm",
+            "../data-files/guardianhash.r1cs",
+            "../data-files/guardianhash_0001.zkey",
+        )
+        .unwrap()
+    }
+
+    fn submit(ctx: &ProvingContext, timeout_ms: u64, sender: &Sender<(u64, Delivery)>) -> u64 {
+        let input = CString::new(example_input()).unwrap();
+        let user_data = sender as *const Sender<(u64, Delivery)> as *mut cty::c_void;
+        let id = unsafe {
+            prove_async_bn254(
+                Some(ctx),
+                input.as_ptr(),
+                timeout_ms,
+                Some(deliver),
+                user_data,
+            )
+        };
+        assert_ne!(0, id);
+        id
+    }
+
+    /// Waits for the callbacks of `count` jobs, and checks that no job calls back twice.
+    fn collect(results: &Receiver<(u64, Delivery)>, count: usize) -> HashMap<u64, Delivery> {
+        let mut delivered = HashMap::new();
+        for _ in 0..count {
+            let (id, delivery) = results.recv_timeout(Duration::from_secs(600)).unwrap();
+            assert!(delivered.insert(id, delivery).is_none());
+        }
+        assert!(results.recv_timeout(Duration::from_secs(1)).is_err());
+        delivered
+    }
+
+    /// The job is removed right after its callback returned.
+    fn wait_unknown(id: u64) {
+        for _ in 0..100 {
+            if job_status_bn254(id) == GROTH16_JOB_UNKNOWN {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("job {} is still listed", id);
+    }
+
+    #[test]
+    fn test_invalid_job() {
+        let input = CString::new("{}").unwrap();
+        let id =
+            unsafe { prove_async_bn254(None, input.as_ptr(), 0, Some(ignore), ptr::null_mut()) };
+        assert_eq!(0, id);
+        assert_eq!(GROTH16_JOB_UNKNOWN, job_status_bn254(1 << 40));
+        assert_eq!(-1, cancel_job_bn254(1 << 40));
+    }
+
+    #[test]
+    fn test_async_job() {
+        let ctx = load();
+        let (sender, results) = channel();
+        let id = submit(&ctx, 0, &sender);
+        assert_ne!(GROTH16_JOB_UNKNOWN, job_status_bn254(id));
+
+        let (status, output, running) = collect(&results, 1).remove(&id).unwrap();
+        assert_eq!(0, status);
+        assert_eq!(GROTH16_JOB_RUNNING, running);
+        assert!(do_verify(&ctx.verifying_key_in_hex(), &output).unwrap());
+        wait_unknown(id);
+    }
+
+    #[test]
+    fn test_cancel_queued_job() {
+        let ctx = load();
+        let (sender, results) = channel();
+        // Occupies every worker, so that the next jobs stay queued.
+        let busy: Vec<_> = (0..WORKER_COUNT.load(Ordering::Relaxed))
+            .map(|_| submit(&ctx, 0, &sender))
+            .collect();
+        let cancelled = submit(&ctx, 0, &sender);
+        let timed_out = submit(&ctx, 1, &sender);
+        assert_eq!(GROTH16_JOB_QUEUED, job_status_bn254(cancelled));
+        assert_eq!(0, cancel_job_bn254(cancelled));
+
+        let delivered = collect(&results, busy.len() + 2);
+        for id in &busy {
+            assert_eq!(0, delivered[id].0);
+        }
+        assert_eq!(-3, delivered[&cancelled].0);
+        assert_eq!(-4, delivered[&timed_out].0);
+        for id in busy.into_iter().chain([cancelled, timed_out]) {
+            wait_unknown(id);
+        }
+    }
+}
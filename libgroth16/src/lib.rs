@@ -1,7 +1,67 @@
+#[cfg(feature = "prover")]
 mod api;
+#[cfg(feature = "prover")]
+mod cancel;
+#[cfg(feature = "prover")]
+mod circuit;
 mod dto;
+mod encoding;
+mod guardian;
+#[cfg(feature = "prover")]
+mod handle;
+#[cfg(feature = "jni")]
+/// cbindgen:ignore
+mod java;
+#[cfg(feature = "prover")]
+mod jobs;
+#[cfg(feature = "prover")]
+mod logging;
 mod proof;
+#[cfg(feature = "python")]
+/// cbindgen:ignore
+mod python;
+#[cfg(feature = "prover")]
+mod registry;
+#[cfg(feature = "prover")]
+mod stats;
+#[cfg(feature = "prover")]
 mod tests;
 mod utils;
+#[cfg(feature = "wasm")]
+/// cbindgen:ignore
+mod wasm;
 
+#[cfg(feature = "prover")]
 pub use api::*;
+#[cfg(feature = "prover")]
+pub use cancel::CancelToken;
+#[cfg(feature = "prover")]
+pub use circuit::{inspect_circuit, read_input_schema, CircuitInfo, InputSignal};
+pub use dto::ProvingOutput;
+pub use encoding::{decode_base64url, from_bytes, from_limbs, to_limbs, to_padded_bytes};
+pub use guardian::{
+    verify_guardian_proof, GuardianInput, GuardianInputBuilder, GuardianStatement,
+    GuardianVerification, HASH_LEN, LIMB_BITS, MAX_JWT_LEN, N_LIMBS, SALT_LEN,
+};
+#[cfg(feature = "prover")]
+pub use handle::*;
+#[cfg(feature = "prover")]
+pub use jobs::*;
+#[cfg(feature = "prover")]
+pub use logging::*;
+pub use proof::{
+    decode_canonical, decode_proof, encode_canonical, encode_proof, ProofEncoding, RapidSnarkProof,
+};
+#[cfg(feature = "prover")]
+pub use registry::{CircuitManifest, CircuitRegistry, CircuitStatus, Manifest, RegistryConfig};
+#[cfg(feature = "prover")]
+pub use stats::ProvingStats;
+pub use utils::{
+    decode_public_input_array, do_verify, do_verify0, do_verify_encoded, failure_cause, serialize,
+    serialize_encoded, FailureCause,
+};
+#[cfg(feature = "prover")]
+pub use utils::{
+    do_prove, do_prove_cancellable, do_prove_with_stats, do_verify_named, load_context,
+    serialize_named, KeyRotation, ProvingContext,
+};
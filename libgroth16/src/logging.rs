@@ -0,0 +1,95 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::ffi::CString;
+use std::sync::{OnceLock, RwLock};
+
+/// Receives a log level, 1 (error) to 5 (trace), and a NUL-terminated message that is only valid during the call. It
+/// may be called from any thread, also concurrently.
+pub type Groth16LogCallback =
+    Option<unsafe extern "C" fn(level: cty::c_int, message: *const cty::c_char)>;
+
+static CALLBACK: RwLock<Groth16LogCallback> = RwLock::new(None);
+/// Whether the callback logger is the global logger; it cannot be when the host already set another one.
+static INSTALLED: OnceLock<bool> = OnceLock::new();
+
+struct CallbackLogger;
+
+static LOGGER: CallbackLogger = CallbackLogger;
+
+impl Log for CallbackLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let callback = *CALLBACK.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(callback) = callback {
+            let message = format!("{}", record.args()).replace('\0', " ");
+            let message = CString::new(message).expect("NUL bytes were replaced");
+            unsafe { callback(record.level() as cty::c_int, message.as_ptr()) };
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn level_filter(level: cty::c_int) -> Option<LevelFilter> {
+    match level {
+        0 => Some(LevelFilter::Off),
+        _ => Level::iter()
+            .find(|l| *l as cty::c_int == level)
+            .map(|l| l.to_level_filter()),
+    }
+}
+
+/// Sends the library's log records up to `level` (0 off, 1 error, 2 warn, 3 info, 4 debug, 5 trace) to `callback`, or
+/// stops logging if it is NULL. Returns -1 for an invalid level, or if the process already has another Rust logger.
+#[no_mangle]
+pub extern "C" fn groth16_set_log_callback(
+    level: cty::c_int,
+    callback: Groth16LogCallback,
+) -> cty::c_int {
+    let Some(filter) = level_filter(level) else {
+        return -1;
+    };
+    if !*INSTALLED.get_or_init(|| log::set_logger(&LOGGER).is_ok()) {
+        return -1;
+    }
+    *CALLBACK.write().unwrap_or_else(|e| e.into_inner()) = callback;
+    log::set_max_level(match callback {
+        Some(_) => filter,
+        None => LevelFilter::Off,
+    });
+    0
+}
+
+#[cfg(test)]
+mod logging_test {
+    use crate::logging::groth16_set_log_callback;
+    use std::ffi::CStr;
+    use std::sync::Mutex;
+
+    static RECORDS: Mutex<Vec<(i32, String)>> = Mutex::new(Vec::new());
+
+    unsafe extern "C" fn record(level: cty::c_int, message: *const cty::c_char) {
+        let message = CStr::from_ptr(message).to_string_lossy().into_owned();
+        RECORDS.lock().unwrap().push((level, message));
+    }
+
+    #[test]
+    fn test_log_callback() {
+        assert_eq!(-1, groth16_set_log_callback(6, Some(record)));
+        assert_eq!(0, groth16_set_log_callback(2, Some(record)));
+        log::warn!("buffer too small");
+        log::info!("not forwarded");
+        assert_eq!(0, groth16_set_log_callback(0, None));
+        log::error!("not forwarded either");
+
+        // Other tests may log concurrently.
+        let records = RECORDS.lock().unwrap();
+        assert!(records.contains(&(2, "buffer too small".to_string())));
+        assert!(!records.iter().any(|(_, m)| m.starts_with("not forwarded")));
+    }
+}
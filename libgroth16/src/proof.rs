@@ -1,16 +1,158 @@
-use ark_bn254::{Bn254, Fq, Fq2, G1Projective, G2Projective};
-use ark_ec::CurveGroup;
+use crate::utils::ParseError;
+use anyhow::Context;
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{AffineRepr, CurveGroup};
 use ark_groth16::Proof;
-use num_traits::Zero;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use num_traits::{One, Zero};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// How a proof, or a verifying key, is written in a proving output. Verifying keys have no JSON encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofEncoding {
+    /// The rapidsnark / snarkjs JSON object, see [`RapidSnarkProof`].
+    Json,
+    /// arkworks compressed serialization in hex, 128 bytes for a proof.
+    CompressedHex,
+    /// arkworks compressed serialization in standard base64.
+    CompressedBase64,
+    /// arkworks uncompressed serialization in hex, 256 bytes for a proof.
+    UncompressedHex,
+}
+
+impl ProofEncoding {
+    /// The code of the encoding in the C API, where 0 stands for auto-detection.
+    pub fn from_code(code: i32) -> Option<Self> {
+        match code {
+            1 => Some(ProofEncoding::Json),
+            2 => Some(ProofEncoding::CompressedHex),
+            3 => Some(ProofEncoding::CompressedBase64),
+            4 => Some(ProofEncoding::UncompressedHex),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ProofEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ProofEncoding::Json),
+            "compressed-hex" => Ok(ProofEncoding::CompressedHex),
+            "compressed-base64" => Ok(ProofEncoding::CompressedBase64),
+            "uncompressed-hex" => Ok(ProofEncoding::UncompressedHex),
+            _ => anyhow::bail!("unknown encoding {}", s),
+        }
+    }
+}
+
+impl Display for ProofEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ProofEncoding::Json => "json",
+            ProofEncoding::CompressedHex => "compressed-hex",
+            ProofEncoding::CompressedBase64 => "compressed-base64",
+            ProofEncoding::UncompressedHex => "uncompressed-hex",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Serializes a proof or a verifying key with one of the binary encodings.
+pub fn encode_canonical<T: CanonicalSerialize>(
+    value: &T,
+    encoding: ProofEncoding,
+) -> anyhow::Result<String> {
+    let mut bytes = Vec::new();
+    match encoding {
+        ProofEncoding::Json => anyhow::bail!("expected a binary encoding"),
+        ProofEncoding::CompressedHex | ProofEncoding::CompressedBase64 => {
+            value.serialize_compressed(&mut bytes)?
+        }
+        ProofEncoding::UncompressedHex => value.serialize_uncompressed(&mut bytes)?,
+    }
+    Ok(match encoding {
+        ProofEncoding::CompressedBase64 => STANDARD.encode(bytes),
+        _ => hex::encode(bytes),
+    })
+}
+
+/// Deserializes a proof or a verifying key. Without an explicit encoding, hex is tried as compressed then uncompressed,
+/// and anything else is taken as compressed base64 (standard or url-safe).
+pub fn decode_canonical<T: CanonicalDeserialize>(
+    value: &str,
+    encoding: Option<ProofEncoding>,
+) -> anyhow::Result<T> {
+    let value = value.trim();
+    let is_hex = value.bytes().all(|b| b.is_ascii_hexdigit());
+    match encoding {
+        None if is_hex => {
+            let bytes = hex::decode(value)?;
+            Ok(T::deserialize_compressed(&*bytes)
+                .or_else(|_| T::deserialize_uncompressed(&*bytes))?)
+        }
+        None | Some(ProofEncoding::CompressedBase64) => {
+            let unpadded = value.trim_end_matches('=');
+            let bytes = STANDARD_NO_PAD
+                .decode(unpadded)
+                .or_else(|_| URL_SAFE_NO_PAD.decode(unpadded))?;
+            Ok(T::deserialize_compressed(&*bytes)?)
+        }
+        Some(ProofEncoding::CompressedHex) => Ok(T::deserialize_compressed(&*hex::decode(value)?)?),
+        Some(ProofEncoding::UncompressedHex) => {
+            Ok(T::deserialize_uncompressed(&*hex::decode(value)?)?)
+        }
+        Some(ProofEncoding::Json) => anyhow::bail!("expected a binary encoding"),
+    }
+}
+
+/// The `proof` field of a proving output: a JSON object, or a string in one of the binary encodings.
+pub fn encode_proof(proof: &Proof<Bn254>, encoding: ProofEncoding) -> anyhow::Result<Value> {
+    match encoding {
+        ProofEncoding::Json => Ok(serde_json::to_value(RapidSnarkProof::from(proof.clone()))?),
+        _ => Ok(Value::String(encode_canonical(proof, encoding)?)),
+    }
+}
+
+/// Reads the `proof` field of a proving output, in the given encoding or any of them.
+pub fn decode_proof(
+    value: &Value,
+    encoding: Option<ProofEncoding>,
+) -> anyhow::Result<Proof<Bn254>> {
+    let proof = match (value, encoding) {
+        (Value::Object(_), None | Some(ProofEncoding::Json)) => {
+            Proof::try_from(serde_json::from_value::<RapidSnarkProof>(value.clone())?)?
+        }
+        (Value::String(value), _) if encoding != Some(ProofEncoding::Json) => {
+            decode_canonical(value, encoding).context(ParseError {
+                message: "invalid proof".to_string(),
+            })?
+        }
+        _ => {
+            return Err(ParseError {
+                message: match encoding {
+                    Some(encoding) => format!("proof is not {}", encoding),
+                    None => "expected a proof object or string".to_string(),
+                },
+            }
+            .into())
+        }
+    };
+    Ok(proof)
+}
 
 #[derive(Debug)]
-pub(crate) struct RapidSnarkProof {
-    pub(crate) pi_a: Vec<Fq>,
-    pub(crate) pi_b: Vec<Vec<Fq>>,
-    pub(crate) pi_c: Vec<Fq>,
-    pub(crate) protocol: String,
+pub struct RapidSnarkProof {
+    pub pi_a: Vec<Fq>,
+    pub pi_b: Vec<Vec<Fq>>,
+    pub pi_c: Vec<Fq>,
+    pub protocol: String,
 }
 
 impl Serialize for RapidSnarkProof {
@@ -18,21 +160,13 @@ impl Serialize for RapidSnarkProof {
     where
         S: serde::Serializer,
     {
-        let pi_a: Vec<String> = self.pi_a.iter().map(|x| x.to_string()).collect();
+        let pi_a: Vec<String> = self.pi_a.iter().map(field_to_string).collect();
         let pi_b: Vec<Vec<String>> = self
             .pi_b
             .iter()
-            .map(|inner| {
-                inner
-                    .iter()
-                    .map(|x| match x.is_zero() {
-                        true => "0".to_string(),
-                        _ => x.to_string(),
-                    })
-                    .collect()
-            })
+            .map(|inner| inner.iter().map(field_to_string).collect())
             .collect();
-        let pi_c: Vec<String> = self.pi_c.iter().map(|x| x.to_string()).collect();
+        let pi_c: Vec<String> = self.pi_c.iter().map(field_to_string).collect();
 
         let json = json!({
             "pi_a": pi_a,
@@ -51,6 +185,12 @@ impl<'de> Deserialize<'de> for RapidSnarkProof {
         D: serde::Deserializer<'de>,
     {
         let json: Value = serde::Deserialize::deserialize(deserializer)?;
+        // A proof in one of the binary encodings.
+        if let Value::String(value) = &json {
+            let proof: Proof<Bn254> = decode_canonical(value, None)
+                .map_err(|_| serde::de::Error::custom("Not a valid encoded proof"))?;
+            return Ok(proof.into());
+        }
         let pi_a: Vec<Fq> = json["pi_a"]
             .as_array()
             .ok_or_else(|| serde::de::Error::custom("Expected pi_a to be an array"))?
@@ -120,59 +260,143 @@ impl<'de> Deserialize<'de> for RapidSnarkProof {
     }
 }
 
+/// Prints a field element in decimal. `Fp`'s `Display` trims leading zeros, which leaves zero as an empty string.
+pub(crate) fn field_to_string<F: Display + Zero>(value: &F) -> String {
+    match value.is_zero() {
+        true => "0".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Affine coordinates with z = 1, as snarkjs and Solidity verifiers expect; the point at infinity is `[0, 1, 0]`.
+fn g1_coordinates(point: &G1Affine) -> Vec<Fq> {
+    match point.xy() {
+        Some((x, y)) => vec![*x, *y, Fq::one()],
+        None => vec![Fq::zero(), Fq::one(), Fq::zero()],
+    }
+}
+
+fn g2_coordinates(point: &G2Affine) -> Vec<Vec<Fq>> {
+    let (x, y, z) = match point.xy() {
+        Some((x, y)) => (*x, *y, Fq2::one()),
+        None => (Fq2::zero(), Fq2::one(), Fq2::zero()),
+    };
+    vec![vec![x.c0, x.c1], vec![y.c0, y.c1], vec![z.c0, z.c1]]
+}
+
+/// A proof that does not describe valid points.
+#[derive(Debug)]
+pub(crate) struct ProofFormatError {
+    message: String,
+}
+
+impl Display for ProofFormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid proof: {}", self.message)
+    }
+}
+
+impl std::error::Error for ProofFormatError {}
+
+fn invalid_proof(message: String) -> ProofFormatError {
+    ProofFormatError { message }
+}
+
+/// Reads affine `[x, y]` or `[x, y, 1]` coordinates as well as Jacobian `[x, y, z]` ones, as written by earlier
+/// versions; z = 0 is the point at infinity.
+fn g1_from_coordinates(name: &str, coordinates: &[Fq]) -> Result<G1Affine, ProofFormatError> {
+    let point = match coordinates {
+        [x, y] => G1Projective::new_unchecked(*x, *y, Fq::one()),
+        [x, y, z] => G1Projective::new_unchecked(*x, *y, *z),
+        _ => {
+            return Err(invalid_proof(format!(
+                "{} has {} coordinates",
+                name,
+                coordinates.len()
+            )))
+        }
+    }
+    .into_affine();
+    if !point.is_on_curve() {
+        return Err(invalid_proof(format!("{} is not on the curve", name)));
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(invalid_proof(format!("{} is not in the subgroup", name)));
+    }
+    Ok(point)
+}
+
+fn g2_from_coordinates(name: &str, coordinates: &[Vec<Fq>]) -> Result<G2Affine, ProofFormatError> {
+    let coordinates: Vec<Fq2> = coordinates
+        .iter()
+        .map(|c| match c[..] {
+            [c0, c1] => Ok(Fq2::new(c0, c1)),
+            _ => Err(invalid_proof(format!(
+                "{} has a coordinate of {} elements",
+                name,
+                c.len()
+            ))),
+        })
+        .collect::<Result<_, _>>()?;
+    let point = match coordinates[..] {
+        [x, y] => G2Projective::new_unchecked(x, y, Fq2::one()),
+        [x, y, z] => G2Projective::new_unchecked(x, y, z),
+        _ => {
+            return Err(invalid_proof(format!(
+                "{} has {} coordinates",
+                name,
+                coordinates.len()
+            )))
+        }
+    }
+    .into_affine();
+    if !point.is_on_curve() {
+        return Err(invalid_proof(format!("{} is not on the curve", name)));
+    }
+    // Unlike G1, the G2 curve has points outside the prime order subgroup.
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(invalid_proof(format!("{} is not in the subgroup", name)));
+    }
+    Ok(point)
+}
+
 impl From<Proof<Bn254>> for RapidSnarkProof {
     fn from(proof: Proof<Bn254>) -> Self {
-        let a = G1Projective::from(proof.a);
-        let b = G2Projective::from(proof.b);
-        let c = G1Projective::from(proof.c);
-        let pi_a = vec![a.x, a.y, a.z];
-        let pi_b = vec![
-            vec![b.x.c0, b.x.c1],
-            vec![b.y.c0, b.y.c1],
-            vec![b.z.c0, b.z.c1],
-        ];
-        let pi_c = vec![c.x, c.y, c.z];
         Self {
-            pi_a,
-            pi_b,
-            pi_c,
+            pi_a: g1_coordinates(&proof.a),
+            pi_b: g2_coordinates(&proof.b),
+            pi_c: g1_coordinates(&proof.c),
             protocol: "groth16".to_string(),
         }
     }
 }
 
-impl From<RapidSnarkProof> for Proof<Bn254> {
-    fn from(val: RapidSnarkProof) -> Self {
-        Proof::<Bn254> {
-            a: G1Projective {
-                x: val.pi_a[0],
-                y: val.pi_a[1],
-                z: val.pi_a[2],
-            }
-            .into_affine(),
-            b: G2Projective {
-                x: Fq2::new(val.pi_b[0][0], val.pi_b[0][1]),
-                y: Fq2::new(val.pi_b[1][0], val.pi_b[1][1]),
-                z: Fq2::new(val.pi_b[2][0], val.pi_b[2][1]),
-            }
-            .into_affine(),
-            c: G1Projective {
-                x: val.pi_c[0],
-                y: val.pi_c[1],
-                z: val.pi_c[2],
-            }
-            .into_affine(),
+impl TryFrom<RapidSnarkProof> for Proof<Bn254> {
+    type Error = anyhow::Error;
+
+    fn try_from(val: RapidSnarkProof) -> Result<Self, Self::Error> {
+        if val.protocol != "groth16" {
+            return Err(invalid_proof(format!("unsupported protocol {}", val.protocol)).into());
         }
+        Ok(Proof::<Bn254> {
+            a: g1_from_coordinates("pi_a", &val.pi_a)?,
+            b: g2_from_coordinates("pi_b", &val.pi_b)?,
+            c: g1_from_coordinates("pi_c", &val.pi_c)?,
+        })
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::proof::RapidSnarkProof;
-    use ark_bn254::Bn254;
+    use crate::proof::{decode_proof, encode_proof, ProofEncoding, RapidSnarkProof};
+    use crate::utils::{failure_cause, FailureCause};
+    use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
+    use ark_ec::AffineRepr;
     use ark_groth16::Proof;
     use ark_serialize::CanonicalDeserialize;
     use ark_serialize::CanonicalSerialize;
+    use num_traits::One;
+    use serde_json::json;
 
     #[test]
     fn test_conversion() {
@@ -180,9 +404,118 @@ mod test {
         let proof = hex::decode(proof_hex).unwrap();
         let proof = Proof::<Bn254>::deserialize_compressed(&*proof).unwrap();
         let proof = RapidSnarkProof::from(proof);
-        let proof: Proof<Bn254> = proof.into();
+        let proof: Proof<Bn254> = proof.try_into().unwrap();
         let mut v = Vec::new();
         let _ = proof.serialize_compressed(&mut v).unwrap();
         assert_eq!(proof_hex, hex::encode(v));
     }
+
+    #[test]
+    fn test_encodings() {
+        let proof_hex = "6b74506effb0f09b3edfff7952ed3971a5fdac3cfc4dd5745e008d4f4883d4af0eaac5c5f4bd927d22763342bd5422f04d3bca54e0f54d3aea7f3f5674d6a61a53fb542dbb912dbb2ebcb55c9e6742dc3e0a658b034e10430b74383b235fe18f5ea0d3e43353313134bfb1d80898de16a6d0bc8c686ae922ccf61b67af9bf922";
+        let proof =
+            Proof::<Bn254>::deserialize_compressed(&*hex::decode(proof_hex).unwrap()).unwrap();
+        for encoding in [
+            ProofEncoding::Json,
+            ProofEncoding::CompressedHex,
+            ProofEncoding::CompressedBase64,
+            ProofEncoding::UncompressedHex,
+        ] {
+            let value = encode_proof(&proof, encoding).unwrap();
+            assert_eq!(proof, decode_proof(&value, None).unwrap());
+            assert_eq!(proof, decode_proof(&value, Some(encoding)).unwrap());
+            assert_eq!(
+                encoding,
+                encoding.to_string().parse::<ProofEncoding>().unwrap()
+            );
+        }
+        assert_eq!(
+            proof_hex,
+            encode_proof(&proof, ProofEncoding::CompressedHex).unwrap()
+        );
+        assert_eq!(
+            172,
+            encode_proof(&proof, ProofEncoding::CompressedBase64)
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .len()
+        );
+
+        let value = encode_proof(&proof, ProofEncoding::CompressedHex).unwrap();
+        assert!(decode_proof(&value, Some(ProofEncoding::Json)).is_err());
+        assert!(decode_proof(&value, Some(ProofEncoding::UncompressedHex)).is_err());
+        let proof: RapidSnarkProof = serde_json::from_value(value).unwrap();
+        assert_eq!("groth16", proof.protocol);
+    }
+
+    #[test]
+    fn test_affine_output() {
+        let proof = Proof::<Bn254> {
+            a: (G1Affine::generator() * ark_bn254::Fr::from(3u8)).into(),
+            b: G2Affine::generator(),
+            c: G1Affine::zero(),
+        };
+        let value = serde_json::to_value(RapidSnarkProof::from(proof.clone())).unwrap();
+        assert_eq!("1", value["pi_a"][2]);
+        assert_eq!(json!(["1", "0"]), value["pi_b"][2]);
+        assert_eq!(json!(["0", "1", "0"]), value["pi_c"]);
+        let decoded: RapidSnarkProof = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(proof, decoded.try_into().unwrap());
+
+        // Affine coordinates without z are read as z = 1.
+        let mut short = value;
+        short["pi_a"].as_array_mut().unwrap().pop();
+        let decoded: RapidSnarkProof = serde_json::from_value(short).unwrap();
+        assert_eq!(proof, decoded.try_into().unwrap());
+    }
+
+    #[test]
+    fn test_invalid_proof() {
+        let proof = Proof::<Bn254> {
+            a: G1Affine::generator(),
+            b: G2Affine::generator(),
+            c: G1Affine::generator(),
+        };
+        let value = serde_json::to_value(RapidSnarkProof::from(proof)).unwrap();
+        let parse = |value: &serde_json::Value| -> anyhow::Result<Proof<Bn254>> {
+            serde_json::from_value::<RapidSnarkProof>(value.clone())?.try_into()
+        };
+        assert!(parse(&value).is_ok());
+
+        let mut short = value.clone();
+        short["pi_a"] = json!(["1"]);
+        let err = parse(&short).unwrap_err();
+        assert_eq!(FailureCause::InvalidInput, failure_cause(&err));
+
+        let mut short = value.clone();
+        short["pi_b"][1] = json!(["1"]);
+        assert!(parse(&short).is_err());
+
+        let mut off_curve = value.clone();
+        off_curve["pi_c"][1] = json!("3");
+        assert!(parse(&off_curve).is_err());
+
+        let mut protocol = value.clone();
+        protocol["protocol"] = json!("plonk");
+        assert!(parse(&protocol).is_err());
+
+        // On the G2 curve, but outside the prime order subgroup.
+        let point = (1u64..)
+            .find_map(|x| {
+                G2Affine::get_point_from_x_unchecked(Fq2::new(Fq::from(x), Fq::one()), false)
+            })
+            .unwrap();
+        assert!(point.is_on_curve());
+        assert!(!point.is_in_correct_subgroup_assuming_on_curve());
+        let mut twisted = value;
+        twisted["pi_b"] = serde_json::to_value(RapidSnarkProof::from(Proof::<Bn254> {
+            a: G1Affine::generator(),
+            b: point,
+            c: G1Affine::generator(),
+        }))
+        .unwrap()["pi_b"]
+            .clone();
+        assert!(parse(&twisted).is_err());
+    }
 }
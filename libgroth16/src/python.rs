@@ -0,0 +1,173 @@
+use crate::utils::{
+    do_prove, do_verify, failure_cause, load_context, serialize, FailureCause, ProvingContext,
+};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyLong, PyString};
+use std::collections::BTreeMap;
+
+create_exception!(
+    groth16,
+    Groth16Error,
+    PyException,
+    "Base class of the library's errors."
+);
+create_exception!(
+    groth16,
+    InvalidArtifactError,
+    Groth16Error,
+    "The wasm, r1cs or zkey file could not be read."
+);
+create_exception!(
+    groth16,
+    InvalidInputError,
+    Groth16Error,
+    "The circuit input or the proving output could not be parsed."
+);
+create_exception!(
+    groth16,
+    InvalidVerifyingKeyError,
+    Groth16Error,
+    "The verifying key could not be decoded."
+);
+create_exception!(
+    groth16,
+    WitnessGenerationError,
+    Groth16Error,
+    "The witness calculator rejected the input."
+);
+create_exception!(
+    groth16,
+    UnsatisfiedConstraintsError,
+    Groth16Error,
+    "The witness does not satisfy the circuit constraints."
+);
+create_exception!(
+    groth16,
+    ProvingError,
+    Groth16Error,
+    "Constraint synthesis or proof generation failed."
+);
+
+fn to_py_err(err: anyhow::Error) -> PyErr {
+    let message = format!("{:#}", err);
+    match failure_cause(&err) {
+        FailureCause::InvalidArtifact => InvalidArtifactError::new_err(message),
+        FailureCause::InvalidInput => InvalidInputError::new_err(message),
+        FailureCause::InvalidVerifyingKey => InvalidVerifyingKeyError::new_err(message),
+        FailureCause::WitnessGeneration => WitnessGenerationError::new_err(message),
+        FailureCause::UnsatisfiedConstraints => UnsatisfiedConstraintsError::new_err(message),
+        FailureCause::Proving => ProvingError::new_err(message),
+        // Neither a registry, a trusted key set nor cancellation is exposed to Python.
+        FailureCause::UnknownCircuit
+        | FailureCause::UntrustedKey
+        | FailureCause::Internal
+        | FailureCause::Cancelled
+        | FailureCause::TimedOut => Groth16Error::new_err(message),
+    }
+}
+
+/// A signal value as a decimal string. `bool` is a subclass of `int`, but would be formatted as `True`.
+fn signal_value(name: &str, value: &PyAny) -> PyResult<String> {
+    if value.is_instance_of::<PyBool>() {
+        return Err(InvalidInputError::new_err(format!(
+            "{} is a bool, expected an int or a decimal string",
+            name
+        )));
+    }
+    Ok(value.str()?.to_str()?.to_owned())
+}
+
+/// Converts a circuit input dict to the JSON that [`do_prove`] takes. Each value is an int or a decimal string, or a
+/// list of them.
+fn input_json(input: &PyDict) -> PyResult<String> {
+    let mut signals = BTreeMap::new();
+    for (name, value) in input.iter() {
+        let name: String = name.extract()?;
+        let values: Vec<String> =
+            if value.is_instance_of::<PyString>() || value.is_instance_of::<PyLong>() {
+                vec![signal_value(&name, value)?]
+            } else {
+                value
+                    .iter()?
+                    .map(|item| signal_value(&name, item?))
+                    .collect::<PyResult<_>>()?
+            };
+        signals.insert(name, values);
+    }
+    serde_json::to_string(&signals).map_err(|e| to_py_err(e.into()))
+}
+
+#[pyclass(name = "ProvingContext", module = "groth16", frozen)]
+struct PyProvingContext {
+    ctx: ProvingContext,
+}
+
+#[pymethods]
+impl PyProvingContext {
+    #[staticmethod]
+    fn load(py: Python<'_>, wasm: &str, r1cs: &str, zkey: &str) -> PyResult<Self> {
+        let ctx = py
+            .allow_threads(|| load_context(wasm, r1cs, zkey))
+            .map_err(to_py_err)?;
+        Ok(PyProvingContext { ctx })
+    }
+
+    /// Proves `input` and returns the proving output as a dict. The GIL is released while proving.
+    fn prove(&self, py: Python<'_>, input: &PyDict) -> PyResult<PyObject> {
+        let input = input_json(input)?;
+        let output = py
+            .allow_threads(|| {
+                let (pub_inputs, proof) = do_prove(&self.ctx, &input)?;
+                serialize(pub_inputs, proof)
+            })
+            .map_err(to_py_err)?;
+        Ok(py.import("json")?.call_method1("loads", (output,))?.into())
+    }
+
+    /// The verifying key in compressed hex.
+    fn verifying_key(&self) -> String {
+        self.ctx.verifying_key_in_hex()
+    }
+}
+
+/// Verifies a proving output, as a dict or a JSON string, against a verifying key.
+#[pyfunction]
+fn verify(py: Python<'_>, vk: &str, output: &PyAny) -> PyResult<bool> {
+    let output: String = match output.downcast::<PyString>() {
+        Ok(output) => output.to_str()?.to_owned(),
+        Err(_) => py
+            .import("json")?
+            .call_method1("dumps", (output,))?
+            .extract()?,
+    };
+    py.allow_threads(|| do_verify(vk, &output))
+        .map_err(to_py_err)
+}
+
+#[pymodule]
+fn groth16(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyProvingContext>()?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    m.add("Groth16Error", py.get_type::<Groth16Error>())?;
+    m.add(
+        "InvalidArtifactError",
+        py.get_type::<InvalidArtifactError>(),
+    )?;
+    m.add("InvalidInputError", py.get_type::<InvalidInputError>())?;
+    m.add(
+        "InvalidVerifyingKeyError",
+        py.get_type::<InvalidVerifyingKeyError>(),
+    )?;
+    m.add(
+        "WitnessGenerationError",
+        py.get_type::<WitnessGenerationError>(),
+    )?;
+    m.add(
+        "UnsatisfiedConstraintsError",
+        py.get_type::<UnsatisfiedConstraintsError>(),
+    )?;
+    m.add("ProvingError", py.get_type::<ProvingError>())?;
+    Ok(())
+}
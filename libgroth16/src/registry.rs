@@ -0,0 +1,389 @@
+use crate::utils::{do_prove, load_context, serialize, ProvingContext};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A file listing the artifacts of one or more circuits. Relative paths are resolved against the manifest's directory.
+#[derive(Deserialize)]
+pub struct Manifest {
+    pub circuits: Vec<CircuitManifest>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct CircuitManifest {
+    pub name: String,
+    pub version: String,
+    pub wasm: String,
+    pub r1cs: String,
+    pub zkey: String,
+    /// Optional circom `.sym` file, for signal names.
+    #[serde(default)]
+    pub sym: Option<String>,
+}
+
+impl CircuitManifest {
+    /// Circuits are addressed as `name@version`.
+    pub fn id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct RegistryConfig {
+    /// Defer loading a circuit until it is first used.
+    pub lazy: bool,
+    /// Evict the least recently used circuits once the artifacts of the loaded ones exceed this many bytes.
+    /// Zero disables the limit.
+    pub max_loaded_bytes: u64,
+}
+
+#[derive(Serialize)]
+pub struct CircuitStatus {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub loaded: bool,
+    /// Known once the circuit has been loaded at least once.
+    pub vk_hash: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct UnknownCircuitError {
+    id: String,
+}
+
+impl Display for UnknownCircuitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "circuit {} is not registered", self.id)
+    }
+}
+
+impl std::error::Error for UnknownCircuitError {}
+
+type Slot = Arc<Mutex<Option<Arc<ProvingContext>>>>;
+
+struct Entry {
+    manifest: CircuitManifest,
+    /// Size of the artifacts on disk, used as an estimate of the memory a loaded circuit takes.
+    size: u64,
+    slot: Slot,
+    vk_hash: Option<String>,
+    last_used: u64,
+}
+
+pub struct CircuitRegistry {
+    config: RegistryConfig,
+    entries: Mutex<HashMap<String, Entry>>,
+    clock: AtomicU64,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl CircuitRegistry {
+    pub fn new(config: RegistryConfig) -> Self {
+        CircuitRegistry {
+            config,
+            entries: Mutex::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// Registers every circuit of a manifest file and returns their ids. The artifacts of every circuit are checked
+    /// before any is registered, and if one fails to load, the circuits registered before it are rolled back, so that a
+    /// manifest is registered either in full or not at all.
+    pub fn register_manifest(&self, path: &str) -> anyhow::Result<Vec<String>> {
+        let content = fs::read_to_string(path).context("failed to read manifest")?;
+        let manifest: Manifest =
+            serde_json::from_str(&content).context("failed to parse manifest")?;
+        let base = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+        let resolve = |file: &str| base.join(file).to_string_lossy().into_owned();
+        let circuits = manifest
+            .circuits
+            .into_iter()
+            .map(|circuit| {
+                let circuit = CircuitManifest {
+                    wasm: resolve(&circuit.wasm),
+                    r1cs: resolve(&circuit.r1cs),
+                    zkey: resolve(&circuit.zkey),
+                    sym: circuit.sym.as_deref().map(resolve),
+                    ..circuit
+                };
+                let size = artifact_size(&circuit)?;
+                Ok((circuit, size))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut registered = Vec::with_capacity(circuits.len());
+        for (circuit, size) in circuits {
+            let (id, slot, previous) = self.insert(circuit, size);
+            registered.push((id.clone(), slot, previous));
+            if let Err(err) = self.load_eagerly(&id) {
+                for (id, slot, previous) in registered.into_iter().rev() {
+                    self.restore(&id, &slot, previous);
+                }
+                return Err(err);
+            }
+        }
+        Ok(registered.into_iter().map(|(id, _, _)| id).collect())
+    }
+
+    /// Registers a circuit, replacing any circuit with the same id, and loads it unless the registry is lazy. When loading
+    /// fails, the circuit it would replace stays registered.
+    pub fn register(&self, manifest: CircuitManifest) -> anyhow::Result<String> {
+        let size = artifact_size(&manifest)?;
+        let (id, slot, previous) = self.insert(manifest, size);
+        if let Err(err) = self.load_eagerly(&id) {
+            self.restore(&id, &slot, previous);
+            return Err(err);
+        }
+        Ok(id)
+    }
+
+    /// Adds an entry for a circuit, and returns its id, its slot and the entry it replaced.
+    fn insert(&self, manifest: CircuitManifest, size: u64) -> (String, Slot, Option<Entry>) {
+        let id = manifest.id();
+        let slot = Arc::new(Mutex::new(None));
+        let entry = Entry {
+            manifest,
+            size,
+            slot: slot.clone(),
+            vk_hash: None,
+            last_used: self.tick(),
+        };
+        let previous = lock(&self.entries).insert(id.clone(), entry);
+        (id, slot, previous)
+    }
+
+    fn load_eagerly(&self, id: &str) -> anyhow::Result<()> {
+        if !self.config.lazy {
+            self.context(id)?;
+        }
+        Ok(())
+    }
+
+    /// Undoes an `insert`, putting back the entry it replaced.
+    fn restore(&self, id: &str, slot: &Slot, previous: Option<Entry>) {
+        let mut entries = lock(&self.entries);
+        // Unless another caller registered the id again meanwhile.
+        if matches!(entries.get(id), Some(entry) if Arc::ptr_eq(&entry.slot, slot)) {
+            match previous {
+                Some(previous) => entries.insert(id.to_string(), previous),
+                None => entries.remove(id),
+            };
+        }
+    }
+
+    /// Returns the context of a circuit, loading it if needed.
+    pub fn context(&self, id: &str) -> anyhow::Result<Arc<ProvingContext>> {
+        let (slot, manifest) = {
+            let mut entries = lock(&self.entries);
+            let entry = entries
+                .get_mut(id)
+                .ok_or_else(|| UnknownCircuitError { id: id.to_string() })?;
+            entry.last_used = self.tick();
+            (entry.slot.clone(), entry.manifest.clone())
+        };
+
+        // Loading takes seconds, so only this circuit's slot is held meanwhile.
+        let (ctx, loaded) = {
+            let mut slot = lock(&slot);
+            match slot.as_ref() {
+                Some(ctx) => (ctx.clone(), false),
+                None => {
+                    let ctx = load_context(&manifest.wasm, &manifest.r1cs, &manifest.zkey)
+                        .with_context(|| format!("failed to load {}", id))?;
+                    if let Some(sym) = &manifest.sym {
+                        ctx.load_symbols(sym)
+                            .with_context(|| format!("failed to load symbols of {}", id))?;
+                    }
+                    let ctx = Arc::new(ctx);
+                    *slot = Some(ctx.clone());
+                    (ctx, true)
+                }
+            }
+        };
+
+        if loaded {
+            let vk_hash = ctx.verifying_key_hash();
+            if let Some(entry) = lock(&self.entries).get_mut(id) {
+                entry.vk_hash = Some(vk_hash);
+            }
+            self.evict(id);
+        }
+        Ok(ctx)
+    }
+
+    pub fn prove(&self, id: &str, input: &str) -> anyhow::Result<String> {
+        let ctx = self.context(id)?;
+        let (pub_inputs, proof) = do_prove(&ctx, input)?;
+        serialize(pub_inputs, proof)
+    }
+
+    pub fn list(&self) -> Vec<CircuitStatus> {
+        let entries = lock(&self.entries);
+        let mut circuits: Vec<_> = entries
+            .iter()
+            .map(|(id, entry)| CircuitStatus {
+                id: id.clone(),
+                name: entry.manifest.name.clone(),
+                version: entry.manifest.version.clone(),
+                loaded: is_loaded(&entry.slot),
+                vk_hash: entry.vk_hash.clone(),
+            })
+            .collect();
+        circuits.sort_by(|a, b| a.id.cmp(&b.id));
+        circuits
+    }
+
+    /// Removes a circuit from the registry. In-flight proofs keep their context until they finish.
+    pub fn unload(&self, id: &str) -> bool {
+        lock(&self.entries).remove(id).is_some()
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Drops least recently used contexts, other than `keep`, until the loaded artifacts fit the limit.
+    fn evict(&self, keep: &str) {
+        if self.config.max_loaded_bytes == 0 {
+            return;
+        }
+        let entries = lock(&self.entries);
+        let mut loaded: Vec<_> = entries
+            .iter()
+            .filter(|(_, entry)| is_loaded(&entry.slot))
+            .collect();
+        let mut total: u64 = loaded.iter().map(|(_, entry)| entry.size).sum();
+        loaded.sort_by_key(|(_, entry)| entry.last_used);
+        for (id, entry) in loaded {
+            if total <= self.config.max_loaded_bytes {
+                break;
+            }
+            if id == keep {
+                continue;
+            }
+            // A slot that is busy is being loaded by another caller; leave it alone.
+            if let Ok(mut slot) = entry.slot.try_lock() {
+                if slot.take().is_some() {
+                    log::info!("evicted circuit {}", id);
+                    total -= entry.size;
+                }
+            }
+        }
+    }
+}
+
+/// Size of a circuit's artifacts on disk, which also checks that they exist.
+fn artifact_size(manifest: &CircuitManifest) -> anyhow::Result<u64> {
+    let mut size = 0;
+    for file in [&manifest.wasm, &manifest.r1cs, &manifest.zkey] {
+        size += fs::metadata(file)
+            .with_context(|| format!("invalid artifact {} for {}", file, manifest.id()))?
+            .len();
+    }
+    Ok(size)
+}
+
+fn is_loaded(slot: &Slot) -> bool {
+    slot.try_lock().map(|slot| slot.is_some()).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod registry_test {
+    use crate::registry::{CircuitRegistry, RegistryConfig};
+    use crate::utils::{failure_cause, FailureCause};
+
+    #[test]
+    fn test_lazy_registry() {
+        let registry = CircuitRegistry::new(RegistryConfig {
+            lazy: true,
+            max_loaded_bytes: 0,
+        });
+        let ids = registry
+            .register_manifest("../data-files/manifest.json")
+            .unwrap();
+        assert_eq!(vec!["guardianhash@0001".to_string()], ids);
+
+        let circuits = registry.list();
+        assert_eq!(1, circuits.len());
+        assert_eq!("guardianhash", circuits[0].name);
+        assert!(!circuits[0].loaded);
+        assert!(circuits[0].vk_hash.is_none());
+
+        assert!(registry.unload("guardianhash@0001"));
+        assert!(!registry.unload("guardianhash@0001"));
+        assert!(registry.prove("guardianhash@0001", "{}").is_err());
+        assert!(registry.list().is_empty());
+    }
+
+    #[test]
+    fn test_broken_manifest() {
+        let registry = CircuitRegistry::new(RegistryConfig {
+            lazy: true,
+            max_loaded_bytes: 0,
+        });
+        // The second circuit's zkey does not exist, so neither circuit is registered.
+        assert!(registry
+            .register_manifest("../data-files/manifest_broken.json")
+            .is_err());
+        assert!(registry.list().is_empty());
+    }
+
+    #[test]
+    fn test_eviction() {
+        let registry = CircuitRegistry::new(RegistryConfig {
+            lazy: true,
+            max_loaded_bytes: 1,
+        });
+        let ids = registry
+            .register_manifest("../data-files/manifest_versions.json")
+            .unwrap();
+        assert_eq!(2, ids.len());
+
+        registry.context(&ids[0]).unwrap();
+        registry.context(&ids[1]).unwrap();
+        let circuits = registry.list();
+        let loaded: Vec<_> = circuits
+            .iter()
+            .filter(|c| c.loaded)
+            .map(|c| &c.id)
+            .collect();
+        assert_eq!(vec![&ids[1]], loaded);
+        assert!(circuits.iter().all(|c| c.vk_hash.is_some()));
+
+        // An evicted circuit is loaded again on use, evicting the other one.
+        registry.context(&ids[0]).unwrap();
+        let loaded: Vec<_> = registry
+            .list()
+            .into_iter()
+            .filter(|c| c.loaded)
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(vec![ids[0].clone()], loaded);
+    }
+
+    #[test]
+    fn test_unload() {
+        let registry = CircuitRegistry::new(RegistryConfig::default());
+        let ids = registry
+            .register_manifest("../data-files/manifest.json")
+            .unwrap();
+        assert!(registry.list()[0].loaded);
+
+        let ctx = registry.context(&ids[0]).unwrap();
+        assert!(registry.unload(&ids[0]));
+        assert!(registry.list().is_empty());
+        let err = registry.prove(&ids[0], "{}").unwrap_err();
+        assert_eq!(FailureCause::UnknownCircuit, failure_cause(&err));
+        // A context taken before the unload stays usable.
+        assert!(!ctx.verifying_key_hash().is_empty());
+    }
+}
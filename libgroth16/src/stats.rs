@@ -0,0 +1,158 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Where the time of one proof went, filled in by `do_prove_with_stats`. Durations are in microseconds.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct ProvingStats {
+    /// Parsing the input JSON and checking it against the circuit.
+    pub parse_us: u64,
+    /// Witness calculation in the circuit's wasm, including the wait for the witness lock.
+    pub witness_us: u64,
+    /// Synthesizing the constraints and checking the witness against them.
+    pub constraints_us: u64,
+    /// `Groth16::prove`, mostly the MSMs and FFTs.
+    pub prove_us: u64,
+    pub total_us: u64,
+    /// Number of field elements in the witness.
+    pub witness_len: u64,
+    /// Highest number of bytes allocated by the process while proving, above what was allocated when the proof started.
+    /// Concurrent proofs share one high-water mark, so theirs are only indicative. Always 0 without the `alloc-stats`
+    /// feature.
+    pub peak_allocation: u64,
+}
+
+/// Measures consecutive phases of a proof.
+pub(crate) struct PhaseTimer {
+    start: Instant,
+    last: Instant,
+    allocated: usize,
+}
+
+impl PhaseTimer {
+    pub(crate) fn start() -> Self {
+        let now = Instant::now();
+        PhaseTimer {
+            start: now,
+            last: now,
+            allocated: alloc::reset_peak(),
+        }
+    }
+
+    /// The duration since the previous phase ended, in microseconds.
+    pub(crate) fn lap(&mut self) -> u64 {
+        let now = Instant::now();
+        let elapsed = micros(now - self.last);
+        self.last = now;
+        elapsed
+    }
+
+    pub(crate) fn finish(self, stats: &mut ProvingStats) {
+        stats.total_us = micros(self.last - self.start);
+        stats.peak_allocation = alloc::peak().saturating_sub(self.allocated) as u64;
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros().try_into().unwrap_or(u64::MAX)
+}
+
+#[cfg(feature = "alloc-stats")]
+mod alloc {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+    static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+    /// The system allocator, counting the bytes currently allocated and their high-water mark.
+    struct CountingAllocator;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                added(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                added(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+                added(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    fn added(size: usize) {
+        let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+    }
+
+    /// Lowers the high-water mark to the current allocation, and returns that.
+    pub(super) fn reset_peak() -> usize {
+        let allocated = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(allocated, Ordering::Relaxed);
+        allocated
+    }
+
+    pub(super) fn peak() -> usize {
+        PEAK.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(not(feature = "alloc-stats"))]
+mod alloc {
+    pub(super) fn reset_peak() -> usize {
+        0
+    }
+
+    pub(super) fn peak() -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod stats_test {
+    use crate::stats::{PhaseTimer, ProvingStats};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn test_phase_timer() {
+        let mut stats = ProvingStats::default();
+        let mut timer = PhaseTimer::start();
+        sleep(Duration::from_millis(2));
+        stats.parse_us = timer.lap();
+        let buffer = vec![1u8; 1 << 20];
+        stats.witness_us = timer.lap();
+        timer.finish(&mut stats);
+
+        assert!(stats.parse_us >= 2000);
+        assert!(stats.total_us >= stats.parse_us + stats.witness_us);
+        if cfg!(feature = "alloc-stats") {
+            assert!(stats.peak_allocation >= buffer.len() as u64);
+        } else {
+            assert_eq!(0, stats.peak_allocation);
+        }
+    }
+}
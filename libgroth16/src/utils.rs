@@ -1,62 +1,213 @@
+#[cfg(feature = "prover")]
+use crate::cancel::{CancelToken, CancelledError, CheckedCircuit, TimedOutError};
+#[cfg(feature = "prover")]
+use crate::circuit::{
+    check_inputs, circuit_info, input_schema, CircuitInfo, InputMismatchError, InputSignal,
+    R1csHeader, SchemaUnavailableError, SymbolTable,
+};
 use crate::dto::ProvingOutput;
+use crate::guardian::UntrustedKeyError;
+#[cfg(feature = "prover")]
+use crate::proof::encode_canonical;
+use crate::proof::{
+    decode_canonical, decode_proof, encode_proof, field_to_string, ProofEncoding, ProofFormatError,
+};
+#[cfg(feature = "prover")]
+use crate::registry::UnknownCircuitError;
+#[cfg(feature = "prover")]
+use crate::stats::{PhaseTimer, ProvingStats};
 use anyhow::Context;
 use ark_bn254::Bn254;
+#[cfg(feature = "prover")]
 use ark_circom::{read_zkey, CircomBuilder, CircomConfig, CircomReduction};
 use ark_ec::pairing::Pairing;
-use ark_groth16::{prepare_verifying_key, Groth16, Proof, ProvingKey, VerifyingKey};
+#[cfg(feature = "prover")]
+use ark_groth16::ProvingKey;
+use ark_groth16::{prepare_verifying_key, Groth16, Proof, VerifyingKey};
+use ark_relations::r1cs::SynthesisError;
+#[cfg(feature = "prover")]
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::CanonicalDeserialize;
+#[cfg(feature = "prover")]
+use ark_serialize::CanonicalSerialize;
 use ark_snark::SNARK;
 use num_bigint::BigInt;
 use num_traits::Num;
+#[cfg(feature = "prover")]
 use rand::thread_rng;
-use std::collections::HashMap;
-use std::fmt::{Debug, Display, Formatter};
+#[cfg(feature = "prover")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "prover")]
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "prover")]
+use std::fmt::Debug;
+use std::fmt::{Display, Formatter};
+#[cfg(feature = "prover")]
 use std::fs::File;
+#[cfg(feature = "prover")]
+use std::sync::{Arc, Mutex, RwLock};
 // use eyre::ContextCompat;
 use ark_ff::PrimeField;
+#[cfg(feature = "prover")]
+use serde::Serialize;
+use serde_json::{json, Value};
 
-pub struct ProvingContext {
+/// The artifacts of one circuit. A context swaps them as a whole on reload, while proofs already running keep theirs.
+#[cfg(feature = "prover")]
+pub(crate) struct ProvingArtifacts {
     pub(crate) cfg: CircomConfig<Bn254>,
     pub(crate) pk: ProvingKey<Bn254>,
+    pub(crate) header: R1csHeader,
+    // Clones of `cfg` share one wasm instance, so witness generation must not run concurrently.
+    pub(crate) witness_lock: Mutex<()>,
 }
 
-impl ProvingContext {
-    pub(crate) fn verifying_key_in_hex(&self) -> String {
+#[cfg(feature = "prover")]
+impl ProvingArtifacts {
+    fn verifying_key_bytes(&self) -> Vec<u8> {
         let mut vk = Vec::new();
         self.pk
             .vk
             .serialize_compressed(&mut vk)
             .expect("failed to serialize the verifying key");
-        hex::encode(vk)
+        vk
     }
 }
 
+#[cfg(feature = "prover")]
+pub struct ProvingContext {
+    artifacts: RwLock<Arc<ProvingArtifacts>>,
+    // Kept across reloads; load them again after reloading a different circuit.
+    symbols: RwLock<Option<Arc<SymbolTable>>>,
+}
+
+/// Verifying key hashes before and after a reload, so that verifiers can be updated in step.
+#[cfg(feature = "prover")]
+#[derive(Serialize)]
+pub struct KeyRotation {
+    pub old_vk_hash: String,
+    pub new_vk_hash: String,
+}
+
+#[cfg(feature = "prover")]
+impl ProvingContext {
+    pub(crate) fn artifacts(&self) -> Arc<ProvingArtifacts> {
+        self.artifacts
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub(crate) fn symbols(&self) -> Option<Arc<SymbolTable>> {
+        self.symbols
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Attaches the signal names of a circom `.sym` file, used to label public signals.
+    pub fn load_symbols(&self, sym_path: &str) -> anyhow::Result<()> {
+        let symbols = SymbolTable::from_file(sym_path)?;
+        *self.symbols.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(symbols));
+        Ok(())
+    }
+
+    pub fn inspect(&self) -> CircuitInfo {
+        circuit_info(&self.artifacts().header, self.symbols().as_deref())
+    }
+
+    /// The inputs the circuit expects. Needs the symbols, see [`ProvingContext::load_symbols`].
+    pub fn input_schema(&self) -> anyhow::Result<Vec<InputSignal>> {
+        let symbols = self.symbols().ok_or(SchemaUnavailableError)?;
+        input_schema(&self.artifacts().header, &symbols)
+    }
+
+    pub fn verifying_key_in_hex(&self) -> String {
+        hex::encode(self.artifacts().verifying_key_bytes())
+    }
+
+    /// The verifying key in one of the binary encodings.
+    pub fn verifying_key_encoded(&self, encoding: ProofEncoding) -> anyhow::Result<String> {
+        encode_canonical(&self.artifacts().pk.vk, encoding)
+    }
+
+    /// SHA-256 of the compressed verifying key, in hex.
+    pub fn verifying_key_hash(&self) -> String {
+        hex::encode(Sha256::digest(self.artifacts().verifying_key_bytes()))
+    }
+
+    /// Loads new artifacts and swaps them in. The context keeps serving proofs with the old artifacts while the new
+    /// ones load; proofs already running finish on the old key and later ones use the new key.
+    pub fn reload(
+        &self,
+        wasm_path: &str,
+        r1cs_path: &str,
+        zkey_path: &str,
+    ) -> anyhow::Result<KeyRotation> {
+        let artifacts = Arc::new(load_artifacts(wasm_path, r1cs_path, zkey_path)?);
+        let new_vk_hash = hex::encode(Sha256::digest(artifacts.verifying_key_bytes()));
+        let old = std::mem::replace(
+            &mut *self.artifacts.write().unwrap_or_else(|e| e.into_inner()),
+            artifacts,
+        );
+        let old_vk_hash = hex::encode(Sha256::digest(old.verifying_key_bytes()));
+        log::info!(
+            "reloaded proving context, verifying key {} -> {}",
+            old_vk_hash,
+            new_vk_hash
+        );
+        Ok(KeyRotation {
+            old_vk_hash,
+            new_vk_hash,
+        })
+    }
+}
+
+#[cfg(feature = "prover")]
 #[derive(Debug)]
 struct InvalidPathError;
 
+#[cfg(feature = "prover")]
 impl Display for InvalidPathError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "path is not valid")
     }
 }
 
+#[cfg(feature = "prover")]
 impl std::error::Error for InvalidPathError {}
 
+#[cfg(feature = "prover")]
 #[derive(Debug)]
 struct BuildError;
 
+#[cfg(feature = "prover")]
 impl Display for BuildError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "path is not valid")
+        write!(f, "witness generation failed")
     }
 }
 
+#[cfg(feature = "prover")]
 impl std::error::Error for BuildError {}
 
+#[cfg(feature = "prover")]
+#[derive(Debug)]
+struct UnsatisfiedError;
+
+#[cfg(feature = "prover")]
+impl Display for UnsatisfiedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "constraints are not satisfied")
+    }
+}
+
+#[cfg(feature = "prover")]
+impl std::error::Error for UnsatisfiedError {}
+
 #[derive(Debug)]
-struct ParseError {
-    message: String,
+pub(crate) struct ParseError {
+    pub(crate) message: String,
 }
 
 impl Display for crate::utils::ParseError {
@@ -67,6 +218,86 @@ impl Display for crate::utils::ParseError {
 
 impl std::error::Error for crate::utils::ParseError {}
 
+/// Coarse classification of a library error, for bindings that need to map failures onto their own error types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureCause {
+    /// The wasm, r1cs or zkey file could not be read, or the `.sym` file a call needs is not loaded.
+    InvalidArtifact,
+    /// The circuit input or the proving output could not be parsed.
+    InvalidInput,
+    /// The verifying key could not be decoded.
+    InvalidVerifyingKey,
+    /// The witness calculator rejected the input.
+    WitnessGeneration,
+    /// The witness does not satisfy the circuit constraints.
+    UnsatisfiedConstraints,
+    /// Constraint synthesis or proof generation failed.
+    Proving,
+    /// No circuit is registered under the requested id.
+    UnknownCircuit,
+    /// The proof is valid but its issuer key is not in the trusted key set.
+    UntrustedKey,
+    /// A panic or an invariant violation inside the library.
+    Internal,
+    /// Proving was stopped through its cancel token.
+    Cancelled,
+    /// Proving ran past the deadline of its cancel token.
+    TimedOut,
+}
+
+pub fn failure_cause(err: &anyhow::Error) -> FailureCause {
+    for cause in err.chain() {
+        if cause.is::<std::io::Error>() {
+            return FailureCause::InvalidArtifact;
+        }
+        if cause.is::<ParseError>()
+            || cause.is::<ProofFormatError>()
+            || cause.is::<serde_json::Error>()
+        {
+            return FailureCause::InvalidInput;
+        }
+        if cause.is::<hex::FromHexError>()
+            || cause.is::<base64::DecodeError>()
+            || cause.is::<ark_serialize::SerializationError>()
+        {
+            return FailureCause::InvalidVerifyingKey;
+        }
+        if cause.is::<SynthesisError>() {
+            return FailureCause::Proving;
+        }
+        if cause.is::<UntrustedKeyError>() {
+            return FailureCause::UntrustedKey;
+        }
+        #[cfg(feature = "prover")]
+        if let Some(cause) = prover_failure_cause(cause) {
+            return cause;
+        }
+    }
+    FailureCause::Internal
+}
+
+#[cfg(feature = "prover")]
+fn prover_failure_cause(cause: &(dyn std::error::Error + 'static)) -> Option<FailureCause> {
+    if cause.is::<InvalidPathError>() || cause.is::<SchemaUnavailableError>() {
+        Some(FailureCause::InvalidArtifact)
+    } else if cause.is::<InputMismatchError>() {
+        Some(FailureCause::InvalidInput)
+    } else if cause.is::<BuildError>() {
+        Some(FailureCause::WitnessGeneration)
+    } else if cause.is::<UnsatisfiedError>() {
+        Some(FailureCause::UnsatisfiedConstraints)
+    } else if cause.is::<UnknownCircuitError>() {
+        Some(FailureCause::UnknownCircuit)
+    } else if cause.is::<CancelledError>() {
+        Some(FailureCause::Cancelled)
+    } else if cause.is::<TimedOutError>() {
+        Some(FailureCause::TimedOut)
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "prover")]
 pub(crate) fn to_vec(vk: *const cty::c_char, vk_len: cty::c_int) -> Vec<u8> {
     unsafe {
         let mut res = Vec::new();
@@ -78,6 +309,7 @@ pub(crate) fn to_vec(vk: *const cty::c_char, vk_len: cty::c_int) -> Vec<u8> {
     }
 }
 
+#[cfg(feature = "prover")]
 pub(crate) fn parse_input(
     input: *const cty::c_char,
     input_len: cty::c_int,
@@ -92,30 +324,53 @@ pub(crate) fn parse_input(
     Ok(inputs_vec)
 }
 
-pub(crate) fn do_verify(vk: &str, proving_output: &str) -> anyhow::Result<bool> {
-    let vk = hex::decode(vk).context("failed to decode VerifyingKey")?;
-    let proving_output: ProvingOutput =
+pub fn do_verify(vk: &str, proving_output: &str) -> anyhow::Result<bool> {
+    do_verify_encoded(vk, proving_output, None)
+}
+
+/// Verifies a proving output whose proof is in `encoding`, or in any encoding if `None`. The verifying key may be hex
+/// or base64, compressed or uncompressed.
+pub fn do_verify_encoded(
+    vk: &str,
+    proving_output: &str,
+    encoding: Option<ProofEncoding>,
+) -> anyhow::Result<bool> {
+    let vk: VerifyingKey<Bn254> =
+        decode_canonical(vk, None).context("failed to decode VerifyingKey")?;
+    let proving_output: Value =
         serde_json::from_str(proving_output).context("failed to decode ProvingOutput")?;
-    let proof = proving_output.proof.into();
-    let inputs = decode_public_input_array(proving_output.public_inputs)?;
-    do_verify0(vk, proof, inputs)
+    let public_inputs: Vec<String> =
+        serde_json::from_value(proving_output["public_inputs"].clone())
+            .context("failed to decode ProvingOutput")?;
+    let proof = decode_proof(&proving_output["proof"], encoding)
+        .context("failed to decode ProvingOutput")?;
+    let inputs = decode_public_input_array(public_inputs)?;
+    verify_with_key(&vk, proof, inputs)
 }
 
-pub(crate) fn do_verify0(
+pub fn do_verify0(
     vk: Vec<u8>,
     proof: Proof<Bn254>,
     inputs: Vec<<Bn254 as Pairing>::ScalarField>,
 ) -> anyhow::Result<bool> {
     let vk = VerifyingKey::<Bn254>::deserialize_compressed(&*vk)?;
-    let pvk = prepare_verifying_key(&vk);
+    verify_with_key(&vk, proof, inputs)
+}
+
+fn verify_with_key(
+    vk: &VerifyingKey<Bn254>,
+    proof: Proof<Bn254>,
+    inputs: Vec<<Bn254 as Pairing>::ScalarField>,
+) -> anyhow::Result<bool> {
+    let pvk = prepare_verifying_key(vk);
     let res = Groth16::<Bn254>::verify_with_processed_vk(&pvk, inputs.as_slice(), &proof)?;
     Ok(res)
 }
 
-pub(crate) fn decode_public_input_array(
+pub fn decode_public_input_array(
     public_inputs: Vec<String>,
 ) -> anyhow::Result<Vec<<Bn254 as Pairing>::ScalarField>> {
-    let inputs: Vec<_> = public_inputs
+    public_inputs
         .iter()
         .enumerate()
         .map(|(i, s)| {
@@ -126,44 +381,44 @@ pub(crate) fn decode_public_input_array(
             let scalar = <Bn254 as Pairing>::ScalarField::from_be_bytes_mod_order(bytes.as_slice());
             Ok::<<Bn254 as Pairing>::ScalarField, ParseError>(scalar)
         })
-        .collect();
-    // let err = inputs.iter().find(|input| {
-    //     if input.is_err() {
-    //         println!("{:?}", input.as_ref().clone().err().unwrap());
-    //     }
-    //     input.is_err()
-    // });
-    // if err.is_some() {
-    //     println!("{:?}", err.unwrap().as_ref().clone().err().unwrap());
-    // }
-    let _ = match inputs.iter().any(|value| value.is_err()) {
-        true => Ok(0),
-        false => Err(ParseError {
-            message: "parse error".to_string(),
-        }),
-    }
-        .context("failed to parse input");
-
-    let inputs = inputs
-        .iter()
-        .map(|value| *value.as_ref().unwrap())
-        .collect();
-    Ok(inputs)
+        .collect::<Result<_, _>>()
+        .context("failed to parse input")
 }
 
-pub(crate) fn load_context(
+#[cfg(feature = "prover")]
+pub fn load_context(
     wasm_path: &str,
     r1cs_path: &str,
     zkey_path: &str,
 ) -> anyhow::Result<ProvingContext> {
+    let artifacts = load_artifacts(wasm_path, r1cs_path, zkey_path)?;
+    Ok(ProvingContext {
+        artifacts: RwLock::new(Arc::new(artifacts)),
+        symbols: RwLock::new(None),
+    })
+}
+
+#[cfg(feature = "prover")]
+fn load_artifacts(
+    wasm_path: &str,
+    r1cs_path: &str,
+    zkey_path: &str,
+) -> anyhow::Result<ProvingArtifacts> {
     let cfg = CircomConfig::new(wasm_path, r1cs_path)
         .map_err(|_| InvalidPathError)
         .context("invalid wasm or r1cs file path")?;
+    let header = R1csHeader::from_file(r1cs_path)?;
     let mut zkey_file = File::open(zkey_path).context("invalid zkey file")?;
     let (pk, _) = read_zkey(&mut zkey_file).context("failed to load zkey")?;
-    Ok(ProvingContext { cfg, pk })
+    Ok(ProvingArtifacts {
+        cfg,
+        pk,
+        header,
+        witness_lock: Mutex::new(()),
+    })
 }
 
+#[cfg(feature = "prover")]
 pub(crate) fn ret_or_err<T, E>(res: Result<T, E>) -> *mut T
     where
         E: Debug + Display,
@@ -174,6 +429,7 @@ pub(crate) fn ret_or_err<T, E>(res: Result<T, E>) -> *mut T
     }
 }
 
+#[cfg(feature = "prover")]
 fn parse_proving_input(input: &str) -> anyhow::Result<HashMap<String, Vec<BigInt>>> {
     let input: HashMap<String, Vec<String>> =
         serde_json::from_str(input).context("failed to parse JSON")?;
@@ -181,10 +437,15 @@ fn parse_proving_input(input: &str) -> anyhow::Result<HashMap<String, Vec<BigInt
     let mut parsed_input = HashMap::new();
 
     for (key, values) in input {
-        let converted_values: Vec<BigInt> = values
-            .into_iter()
-            .map(|s| BigInt::from_str_radix(&s, 10).unwrap_or_else(|_| BigInt::from(0)))
-            .collect();
+        let converted_values = values
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                BigInt::from_str_radix(s, 10).map_err(|_| ParseError {
+                    message: format!("{}[{}] is not a decimal integer: {:?}", key, i, s),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         parsed_input.insert(key, converted_values);
     }
@@ -192,40 +453,134 @@ fn parse_proving_input(input: &str) -> anyhow::Result<HashMap<String, Vec<BigInt
     Ok(parsed_input)
 }
 
-pub(crate) fn do_prove(
+#[cfg(feature = "prover")]
+pub fn do_prove(
+    ctx: &ProvingContext,
+    input: &str,
+) -> anyhow::Result<(Vec<<Bn254 as Pairing>::ScalarField>, Proof<Bn254>)> {
+    let (pub_inputs, proof, _) = do_prove_with_stats(ctx, input)?;
+    Ok((pub_inputs, proof))
+}
+
+/// Like [`do_prove`], and also reports how long each phase took.
+#[cfg(feature = "prover")]
+pub fn do_prove_with_stats(
+    ctx: &ProvingContext,
+    input: &str,
+) -> anyhow::Result<(
+    Vec<<Bn254 as Pairing>::ScalarField>,
+    Proof<Bn254>,
+    ProvingStats,
+)> {
+    prove_phases(&ctx.artifacts(), ctx.symbols().as_deref(), input, None)
+}
+
+/// Like [`do_prove`], and stops between phases once `token` fires, see [`CancelToken`].
+#[cfg(feature = "prover")]
+pub fn do_prove_cancellable(
     ctx: &ProvingContext,
     input: &str,
+    token: &CancelToken,
 ) -> anyhow::Result<(Vec<<Bn254 as Pairing>::ScalarField>, Proof<Bn254>)> {
+    let (pub_inputs, proof, _) = prove_phases(
+        &ctx.artifacts(),
+        ctx.symbols().as_deref(),
+        input,
+        Some(token),
+    )?;
+    Ok((pub_inputs, proof))
+}
+
+#[cfg(feature = "prover")]
+fn checkpoint(token: Option<&CancelToken>) -> anyhow::Result<()> {
+    match token {
+        Some(token) => token.check(),
+        None => Ok(()),
+    }
+}
+
+/// Proves with the artifacts and symbols of a context, taken by the caller so that they outlive a reload.
+#[cfg(feature = "prover")]
+pub(crate) fn prove_phases(
+    artifacts: &ProvingArtifacts,
+    symbols: Option<&SymbolTable>,
+    input: &str,
+    token: Option<&CancelToken>,
+) -> anyhow::Result<(
+    Vec<<Bn254 as Pairing>::ScalarField>,
+    Proof<Bn254>,
+    ProvingStats,
+)> {
+    let mut stats = ProvingStats::default();
+    let mut timer = PhaseTimer::start();
     let input = parse_proving_input(input).context("failed to parse input")?;
-    let mut builder = CircomBuilder::new(ctx.cfg.clone());
+    // Checked up front, as the witness calculator fails opaquely (or panics) on mismatched inputs. The wasm only knows
+    // its inputs by a hash of their name, so the check needs the symbols.
+    if let Some(symbols) = symbols {
+        let schema = input_schema(&artifacts.header, symbols)?;
+        check_inputs(
+            &schema,
+            input.iter().map(|(key, value)| (key.as_str(), value.len())),
+        )
+        .context("invalid input")?;
+    }
+    stats.parse_us = timer.lap();
+    let mut builder = CircomBuilder::new(artifacts.cfg.clone());
     for (key, value) in input.iter() {
         for item in value {
             builder.push_input(key, item.clone());
         }
     }
 
-    let circom = builder
-        .build()
-        .map_err(|_| BuildError)
-        .context("failed to build circuit")?;
+    let circom = {
+        let _guard = artifacts
+            .witness_lock
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        // Abandoned requests may have queued on the lock.
+        checkpoint(token)?;
+        builder.build()
+    }
+    .map_err(|_| BuildError)
+    .context("failed to build circuit")?;
 
     let pub_inputs = circom
         .get_public_inputs()
         .context("failed to get public inputs")?;
+    stats.witness_us = timer.lap();
+    stats.witness_len = circom.witness.as_ref().map_or(0, |w| w.len() as u64);
+    checkpoint(token)?;
 
     let mut rng = thread_rng();
 
     let cs = ConstraintSystem::<<Bn254 as Pairing>::ScalarField>::new_ref();
-    circom.clone().generate_constraints(cs.clone()).unwrap();
-    let is_satisfied = cs.is_satisfied().unwrap();
-    assert!(is_satisfied);
-
-    let proof = Groth16::<Bn254, CircomReduction>::prove(&ctx.pk, circom, &mut rng)
-        .context("failed to produce proof")?;
+    circom
+        .clone()
+        .generate_constraints(cs.clone())
+        .context("failed to generate constraints")?;
+    if !cs.is_satisfied().context("failed to check constraints")? {
+        return Err(UnsatisfiedError).context("invalid witness");
+    }
+    stats.constraints_us = timer.lap();
+    checkpoint(token)?;
 
-    Ok((pub_inputs, proof))
+    let circuit = CheckedCircuit {
+        circuit: circom,
+        token,
+    };
+    let proof = Groth16::<Bn254, CircomReduction>::prove(&artifacts.pk, circuit, &mut rng)
+        .or_else(|err| {
+            checkpoint(token)?;
+            Err(err).context("failed to produce proof")
+        })?;
+    stats.prove_us = timer.lap();
+    timer.finish(&mut stats);
+    log::debug!("proved in {}us: {:?}", stats.total_us, stats);
+
+    Ok((pub_inputs, proof, stats))
 }
 
+#[cfg(feature = "prover")]
 pub(crate) fn write_to_buffer(
     output: &String,
     buf: *mut cty::c_char,
@@ -241,28 +596,123 @@ pub(crate) fn write_to_buffer(
         }
         len_c_int
     } else {
-        println!("required length is {}", len_c_int);
+        log::warn!("buffer too small, required length is {}", len_c_int);
         -1000
     }
 }
 
-pub(crate) fn serialize(
+/// Like [`serialize`], with the proof in the given encoding.
+pub fn serialize_encoded(
+    public_inputs: Vec<<Bn254 as Pairing>::ScalarField>,
+    proof: Proof<Bn254>,
+    encoding: ProofEncoding,
+) -> anyhow::Result<String> {
+    let output = json!({
+        "public_inputs": public_inputs.iter().map(field_to_string).collect::<Vec<_>>(),
+        "proof": encode_proof(&proof, encoding)?,
+    });
+    Ok(output.to_string())
+}
+
+pub fn serialize(
+    public_inputs: Vec<<Bn254 as Pairing>::ScalarField>,
+    proof: Proof<Bn254>,
+) -> anyhow::Result<String> {
+    let output = ProvingOutput {
+        public_inputs: public_inputs.iter().map(field_to_string).collect(),
+        named_public_inputs: None,
+        proof: proof.into(),
+    };
+    let output = serde_json::to_string(&output).expect("failed to serialize to output");
+    Ok(output)
+}
+
+/// Like [`serialize`], and also maps each public signal name of the context's symbol table to its value.
+#[cfg(feature = "prover")]
+pub fn serialize_named(
+    ctx: &ProvingContext,
     public_inputs: Vec<<Bn254 as Pairing>::ScalarField>,
     proof: Proof<Bn254>,
 ) -> anyhow::Result<String> {
+    let names = public_signal_names(ctx)?;
+    let public_inputs: Vec<String> = public_inputs.iter().map(field_to_string).collect();
+    anyhow::ensure!(
+        names.len() == public_inputs.len(),
+        "circuit has {} public signals but the proof has {}",
+        names.len(),
+        public_inputs.len()
+    );
     let output = ProvingOutput {
-        public_inputs: public_inputs.iter().map(|v| v.to_string()).collect(),
+        named_public_inputs: Some(names.into_iter().zip(public_inputs.clone()).collect()),
+        public_inputs,
         proof: proof.into(),
     };
     let output = serde_json::to_string(&output).expect("failed to serialize to output");
     Ok(output)
 }
 
-#[cfg(test)]
+#[cfg(feature = "prover")]
+fn public_signal_names(ctx: &ProvingContext) -> anyhow::Result<Vec<String>> {
+    let signals = ctx
+        .inspect()
+        .public_signals
+        .context("no symbols are loaded for the circuit")?;
+    signals
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| name.with_context(|| format!("public signal {} has no name", i)))
+        .collect()
+}
+
+/// Orders named public signal values by the circuit's public signal names.
+#[cfg(feature = "prover")]
+fn order_named_inputs(
+    names: &[String],
+    named: &BTreeMap<String, String>,
+) -> anyhow::Result<Vec<String>> {
+    if let Some(unknown) = named.keys().find(|name| !names.contains(name)) {
+        return Err(ParseError {
+            message: format!("unknown public signal {}", unknown),
+        })
+        .context("failed to parse input");
+    }
+    names
+        .iter()
+        .map(|name| {
+            named.get(name).cloned().ok_or_else(|| ParseError {
+                message: format!("missing public signal {}", name),
+            })
+        })
+        .collect::<Result<_, _>>()
+        .context("failed to parse input")
+}
+
+/// Verifies a proving output whose `named_public_inputs` are keyed by signal name, against the context's verifying
+/// key. The values are put in circuit order using the context's symbol table, so `public_inputs` may be omitted.
+#[cfg(feature = "prover")]
+pub fn do_verify_named(ctx: &ProvingContext, proving_output: &str) -> anyhow::Result<bool> {
+    let proving_output: ProvingOutput =
+        serde_json::from_str(proving_output).context("failed to decode ProvingOutput")?;
+    let named = proving_output
+        .named_public_inputs
+        .context("expected named_public_inputs")?;
+    let public_inputs = order_named_inputs(&public_signal_names(ctx)?, &named)?;
+    let inputs = decode_public_input_array(public_inputs)?;
+    do_verify0(
+        ctx.artifacts().verifying_key_bytes(),
+        proving_output.proof.try_into()?,
+        inputs,
+    )
+}
+
+#[cfg(all(test, feature = "prover"))]
 mod utils_test {
-    use crate::utils::{do_prove, do_verify, load_context, parse_proving_input, serialize};
-    use ark_bn254::Bn254;
+    use crate::utils::{
+        decode_public_input_array, do_prove, do_verify, failure_cause, load_context,
+        order_named_inputs, parse_proving_input, serialize, FailureCause,
+    };
     use itertools::Itertools;
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_parse_proving_input() {
@@ -294,6 +744,10 @@ mod utils_test {
             "5841544268561861499519250994748571,282086110796185156675799806248152448",
             v2
         );
+
+        let err = parse_proving_input(r#"{"key1": ["123", "0x1c8"]}"#).unwrap_err();
+        assert_eq!(FailureCause::InvalidInput, failure_cause(&err));
+        assert!(err.to_string().contains("key1[1]"));
     }
 
     #[test]
@@ -324,12 +778,85 @@ mod utils_test {
         println!("{}", vk);
     }
 
+    #[test]
+    fn test_reload() {
+        let ctx = load_context(
+            "../data-files/guardianhash.wasm",
+            "../data-files/guardianhash.r1cs",
+            "../data-files/guardianhash_0001.zkey",
+        )
+        .unwrap();
+        let vk_hash = ctx.verifying_key_hash();
+        let rotation = ctx
+            .reload(
+                "../data-files/guardianhash.wasm",
+                "../data-files/guardianhash.r1cs",
+                "../data-files/guardianhash_0001.zkey",
+            )
+            .unwrap();
+        assert_eq!(vk_hash, rotation.old_vk_hash);
+        assert_eq!(vk_hash, rotation.new_vk_hash);
+
+        assert!(ctx
+            .reload(
+                "../data-files/guardianhash.wasm",
+                "../data-files/guardianhash.r1cs",
+                "../data-files/missing.zkey",
+            )
+            .is_err());
+        assert_eq!(vk_hash, ctx.verifying_key_hash());
+    }
+
     #[test]
     fn test_verify() {
         let proving_output = "{\"public_inputs\":[\"95\",\"49\",\"58\",\"6\",\"195\",\"116\",\"113\",\"221\",\"31\",\"181\",\"224\",\"199\",\"58\",\"220\",\"110\",\"223\",\"30\",\"242\",\"192\",\"153\",\"210\",\"191\",\"245\",\"71\",\"155\",\"73\",\"215\",\"220\",\"204\",\"102\",\"42\",\"108\",\"5841544268561861499519250994748571\",\"282086110796185156675799806248152448\",\"2181169572700087019903500222780233598\",\"1322589976114836556068768894837633649\",\"1794113848426178665483863008905364300\",\"543380795324313410170505147425740531\",\"1493214249295981343844955353860051664\",\"2171199579242924905862250512208697455\",\"1395394319132308840130123038054629304\",\"1562009664380263536909338779810969578\",\"1594567849407226969396248621216777848\",\"2058356264851095114515728757906168363\",\"836769104848661443299826291369000556\",\"1779001964758400339025173335511101862\",\"2544058187525854999124570613534759403\",\"424565350689075956046563544271353450\",\"3799511822475913352444008446631779\",\"97\",\"54\",\"55\",\"55\",\"57\",\"57\",\"57\",\"51\",\"57\",\"54\",\"100\",\"99\",\"52\",\"57\",\"97\",\"50\",\"56\",\"97\",\"100\",\"54\",\"99\",\"57\",\"99\",\"50\",\"52\",\"50\",\"55\",\"49\",\"57\",\"98\",\"98\",\"51\"],\"proof\":\"aaf87197971c2fbba7550f621add868c1f5c65ef2d9e11e66eeb93fa9192c59f4759088dac8abfcc5a9443b205b2cba11659836d1fe76214f28050e0b565511da494af12152309a16eb3bd862cf2ff43dc80497f2c1a2aa8e53db48a6bb69c84756f0ed1c534c0c2e926fd3dadcfa5f433f765773b885f207a99635516c79d9e\"}\n";
         let vk = "987eb6f620cbd00941204ec4f6a81a46419373a821c8ffd9affca1291900631ffeca146164a8f8cada7dd266805f0f0d406158686ebab25caf020ec28a02c6073dbbf2228db69a59b85c97eced983f4189e8ecb6397838d0bea80eb50af98800edf692d95cbdde46ddda5ef7d422436779445c5e66006a42761e1f12efde0018c212f3aeb785e49712e7a9353349aaf1255dfb31b7bf60723a480d9293938e19c9809249c6849563b6de34d0b1e119676bf904cd536b6df9a91eb2b788c6e02b553d1ce7c10bc6c904176abbae5f92ea87217214f849f84b4c49b1ed660576ac52000000000000009c151f6e98a6eeba3e77eb083a5530c7f6dc57f73ee5c4cb2607328a0786ac877763c5ed08a9ca3a22be657458d0abe287e1e90eeb090eafdaa015fe5b8fd3af5daaa2fdb341443764c35cdd224dea08328d679f1f3c51a924b8f2169c967f0cd8294c5a3eee3658a7ea37d03dabc896166e7511bd358b1103b954eb40b6322f30e5860d9355842fc24ecc6d9d552de4fd96b94b10a1637acaa345856c9b99965fef99948868fcf9371dade7eb884332e406b862f2ad3c707000dfc00afa759776504a0be7d95f263fc3eeb05182fe13e87a454de725234f00b09f066e67d4988b9cec1068f188de4b159c67e9179122a24f7cbb08a60e8d85d1f14b30cda59718097eb24e6848b5259007f899c6c9cfd74a6f80d83012ba9d8d81cf749b9f94c3678ff9c27b3819790a8f34aac6f7a1dcb8c64b0849e556907cf9b8bb03c814a83342e1a1ec91cfc09040cdac4860e4bf58eab54cf6683915385273bee047043fff7233f542b5d2e7f253441c5510feb73d6556f4e82cec7fa1aae962928590a89fd7e4ab8c0acec7e987934ccc82613cf27cc7877d4ffba7292964c5196b8b3c59d35f65bef53f79d8859d885c7812f3990c178d11972915db542a4e5491843462343bd1751c0e20600fadec3452bceabb40b16cbf690ec08d3136fc531901f1794a95c305f59c5eb0f08020133677631e6c63eba360e8210e0900011d198a33d592db0b3d1ca7de038df1c7beefc126fd54edde961b5ccacfd0e7c6a63a1f2541ab8a0f4b1a5fef017c61db38c07ce6293c60444833a3d3f559388322440da9d8023d27b01352484ecb3b584234aea7c4864c2a9636281a780f4ca72a88130e313f20ad8d1be5d7fcb0d8c3f19a7947e175276e9e4fdf2662dd69d40707a13dc6fbfa476630aed4d4765c16ed498193c13f101b7bc026c8cdf0e1e33224a72f49b1838a9faae596b3a5000f58a853c4357ec6e513c7c72aed53443b3f2799ebea44e164adc686e984e421bd2658aa7e2dcaed5e8f7679e49135645ba764036343868a918febf74f0582a67a3ac12fe45054be22897d1032ae3425d7957984eea1f42137dcb6e61b2bd0fcd999f70a1dda03eb5ba491b7d5c728a5d5ab0dad5170f3f89fc588a96fb9e352630ef658612b90cd70cef388c901e500567fd49a788657ef607b53faeeb3f2f3044ac411d74383312c3ec16d34c60d7380432791c7a9596772efa9f9488af5628731110da0ac5981381e2c50718e772354f1b7886450951b83e94b65218b12f49c33fec3fc5da0adf91180ac471841b00245200d7a7d36022bd3436e441ee98cfa2e15478dbdd484ddb8c75b5346a48b3005c517ccdf78088e0e205bb0ac382782934f64d634902926027e489ee6aced666e6623f2e13d7bddb461c0390afab9b66d018a190f4acc83e53b6b43cdd6149fe704875f4a251912f5ebbe60213893498d25b497b576f97a2ba9abbfa2f882cc3da1088bfb7e0681cc3df70b4f4be3101ac68ee75db080c60b1489bc74b83cd0b45697fffa8f54a41231e46f48fd240ded208cf24128d1ff5071085574d2488c517c9011d3940924f7ac57a4d9123c7a9ae81592f9e707cb8353476944895c52c7d483bfa83e6f242b867eda6f2f4e483c6028c5ddd82f61b6e96a9d841a176ca7691ee06f99bb78565dd1efc2962ccf825acc22d5a1af5c72a365e60f443c0307570e1ee665d20803ade104d1a982dace277f0ada2bbae8ded842bab4c80d2033b8935ccfd3f6a2eb4543b8a141eabcc11ce6ff080e88fd77794aabfeaf4b649c6687e30e943e1532ba877ba8ec58fcf83933600de41b1d39d697f0cdeb0580bdf72004d57cb34375c3e93ed9a6c8cd57fc6505e72898e0019de2273aa3d12bef1b82f246a5fbf87e1ad673679ebc8d43927221f43b9be4f5fdd3c295392e61d8b08e7d15ee1a673a72b2f7d550bcd790dafbb0abf05afd53269e272f77eac01045a9d7a57591ee749e32e503beeb087d85d422d8b20d3bd33ea81b69cbf948413113441fc46ded8012287fb616c50ce0644c26ad260700c3a84090f5012d55640321f863f611e32c8c86323b6447e9667302fcea4bf4a7295ad8a780e8556200f386e136cbd88d2c4c0f55bece8ec448ec97bdcb228d966b1ae1d6b90fd04d151827d23192a81ba92ecc6497ae4efe29816c9d3ff6f59c86790f9d2cef3c6512df054cd6b592f8b22ab99c6feec5b5769854628e982696d181edbd7af51c742fe0ad556719dc5d058af8323d5ad2e1ced663f6d651e7270c48cb754fc8c7dc54bea6ea5066168d83a1dbaec797269e2a27854b29efadca48f45810a5981a9487c3963e08aaa9c753625d6e1bf7e0c7715b742b499eec6024278661d81e3966c34b808958cd41dd4231b73a7c83b53b7952a7662ee23fc992dbe1762e407599e967a2a0dc4a1460b2696271c18bf9a4a2ff81033d091c3b5dcf159355f5d80b2f2416881f87f36250f8b698c54ed578ddba66ea2e843139f53bda78227f94e128bbacf2a55aca6aa45c57c1b8d37f22bdf1f43b33416e10154aaeb5b066197c7a10a0aa1ada15d121772458db8a2cceb2246f1989ae8034b0834ba046cc0b3b2aeb2e5f1a4509689e6f90247ba522a7750ec45dcfb8ccd6cbb96914094e6d22593c94d45a8431e43a878190cb76172c8352b1498210804dfd17d90c863f4e2f38ad834d5ba68786bcb8a6f23620f80cfda6a0897223c2e8cc65c816db7a183e8ed9a201f5052c550006e73b82e84b5aa7c68a03963589bbfa7211ebb94eb6ea67f0afa8c377537392cff65e6d387c706a132061e951a618e86fde840418dfb530c78fc62bb5f11bd7d02de011108af6c5292650aa5defc8681c6e665d64765a4b361e54beda792c256126fa7d20bd8d1196dbd8df7280de28df371c71676ff6119d858dfe4d89958facd30a83f85cbbc1f5e0f033e5bec0cbbf5776b6b5dc042c02160d15ccb8817ca29c1de85c9bd0c6a9324f6e841db10381d6c37e984c90f6ec03c21ae7fc923c1d5d4b170715e5b8b6c3967e747f32023130b3d0cb942aff490603bb27f75c35974824fd0d1576e89107b94f389aa7fa4208f85b6aac2236ad128df3e1462a58df316b940a3eaf5abfd60b982bfa66eb82cafe400b2c1691a109ec262956d6ae8efea99d744494919388555c3b618e32da802106f772084b6d991a4c936e43c09aea0f94e58faeba8443a4dec1b4e614409c158d60ac30d8f51b457eba9976cf41f808d9c6e354d8dd96ecabc688495ad7a1527c14c3bac4ff0c44d78d865fff4458e1ba692519067646a05f7cd000ac2713da8533fd2e1a7b2742f306019c0ff40dd7977af2955491b38a968535d45800fe4b1de5dd1fd57d841ef0aa6de5399ebb62890442be384064521e0714f1ce79d0ddb11d9a653bd118e9a1fdbd65b537c72073764441c403a9970bdaf492df2a3467f981b1f1cb9c8176edb4360a812072f49826c90c0bb9d6f48a9fe26b3801acd4017522614f2fa45921dc29ba4eed20a5fee59f8ea036eb378ccd88f528dd568b04444a8987ac0285fe133836cc09f79277687a4cd07768573e607e5fdd249ff663da3f59d9bcaf1aca98a2b2f7eb662b2554137368f9d44865a4a6ef4f59ff8054e3ee984a48820a02793050f9a638be35964963ac8efb847b056e185f548c88c59b282d27650b";
-        let res = do_verify::<Bn254>(vk, proving_output);
+        let res = do_verify(vk, proving_output);
         assert!(res.is_ok());
         assert!(res.unwrap());
     }
+
+    #[test]
+    fn test_failure_cause() {
+        let err =
+            decode_public_input_array(vec!["12".to_string(), "0x12".to_string()]).unwrap_err();
+        assert_eq!(FailureCause::InvalidInput, failure_cause(&err));
+
+        let err = do_verify("not hex", "{}").unwrap_err();
+        assert_eq!(FailureCause::InvalidVerifyingKey, failure_cause(&err));
+
+        let err = load_context(
+            "../data-files/guardianhash.wasm",
+            "../data-files/missing.r1cs",
+            "../data-files/guardianhash_0001.zkey",
+        )
+        .err()
+        .unwrap();
+        assert_eq!(FailureCause::InvalidArtifact, failure_cause(&err));
+    }
+
+    #[test]
+    fn test_order_named_inputs() {
+        let names = vec![
+            "main.out".to_string(),
+            "main.pubkey[0]".to_string(),
+            "main.pubkey[1]".to_string(),
+        ];
+        let mut named = BTreeMap::new();
+        named.insert("main.pubkey[1]".to_string(), "3".to_string());
+        named.insert("main.out".to_string(), "1".to_string());
+        named.insert("main.pubkey[0]".to_string(), "2".to_string());
+        assert_eq!(
+            vec!["1", "2", "3"],
+            order_named_inputs(&names, &named).unwrap()
+        );
+
+        named.insert("main.salt[0]".to_string(), "4".to_string());
+        let err = order_named_inputs(&names, &named).unwrap_err();
+        assert_eq!(FailureCause::InvalidInput, failure_cause(&err));
+
+        named.remove("main.salt[0]");
+        named.remove("main.out");
+        assert!(order_named_inputs(&names, &named).is_err());
+    }
 }
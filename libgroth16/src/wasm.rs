@@ -0,0 +1,43 @@
+use crate::proof::{decode_canonical, decode_proof, encode_canonical, encode_proof, ProofEncoding};
+use crate::utils::do_verify;
+use ark_bn254::Bn254;
+use ark_groth16::VerifyingKey;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+fn js_error(err: anyhow::Error) -> JsError {
+    JsError::new(&format!("{:#}", err))
+}
+
+/// Verifies a proving output JSON against a verifying key, see [`do_verify`]. Throws if either cannot be decoded.
+#[wasm_bindgen]
+pub fn verify(vk: &str, proving_output: &str) -> Result<bool, JsError> {
+    do_verify(vk, proving_output).map_err(js_error)
+}
+
+/// Re-encodes a verifying key in `to`, one of the binary [`ProofEncoding`] names.
+#[wasm_bindgen(js_name = convertVerifyingKey)]
+pub fn convert_verifying_key(vk: &str, to: &str) -> Result<String, JsError> {
+    let to: ProofEncoding = to.parse().map_err(js_error)?;
+    let vk: VerifyingKey<Bn254> = decode_canonical(vk, None).map_err(js_error)?;
+    encode_canonical(&vk, to).map_err(js_error)
+}
+
+/// Re-encodes a proof in `to`. The proof may be a rapidsnark JSON proof, a whole proving output or an encoded string;
+/// JSON is returned as a string too.
+#[wasm_bindgen(js_name = convertProof)]
+pub fn convert_proof(proof: &str, to: &str) -> Result<String, JsError> {
+    let to: ProofEncoding = to.parse().map_err(js_error)?;
+    let mut value = match proof.trim_start().starts_with('{') {
+        true => serde_json::from_str(proof).map_err(|e| js_error(e.into()))?,
+        false => Value::String(proof.trim().to_string()),
+    };
+    if let Some(inner) = value.get_mut("proof") {
+        value = inner.take();
+    }
+    let proof = decode_proof(&value, None).map_err(js_error)?;
+    match encode_proof(&proof, to).map_err(js_error)? {
+        Value::String(encoded) => Ok(encoded),
+        json => Ok(json.to_string()),
+    }
+}
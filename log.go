@@ -0,0 +1,56 @@
+package go_groth16
+
+/*
+#cgo CFLAGS:-I${SRCDIR}/libgroth16/include
+#include <groth16.h>
+
+void goGroth16Log(int level, char *message);
+*/
+import "C"
+import (
+	"errors"
+	"sync"
+)
+
+// LogLevel is the severity of a libgroth16 log record.
+type LogLevel int
+
+const (
+	LogOff LogLevel = iota
+	LogError
+	LogWarn
+	LogInfo
+	LogDebug
+	LogTrace
+)
+
+var (
+	loggerMu sync.RWMutex
+	logger   func(LogLevel, string)
+)
+
+//export goGroth16Log
+func goGroth16Log(level C.int, message *C.char) {
+	loggerMu.RLock()
+	defer loggerMu.RUnlock()
+	if logger != nil {
+		logger(LogLevel(level), C.GoString(message))
+	}
+}
+
+// SetLogger sends the library's log records up to level to fn, which may be called from any goroutine. A nil fn or
+// LogOff disables logging.
+func SetLogger(level LogLevel, fn func(LogLevel, string)) error {
+	loggerMu.Lock()
+	logger = fn
+	loggerMu.Unlock()
+
+	callback := C.Groth16LogCallback(C.goGroth16Log)
+	if fn == nil {
+		callback = nil
+	}
+	if C.groth16_set_log_callback(C.int(level), callback) != 0 {
+		return errors.New("libgroth16 logging could not be configured")
+	}
+	return nil
+}